@@ -0,0 +1,120 @@
+use godot::{classes::CanvasModulate, prelude::*};
+
+use crate::map::DayMem;
+
+// Others poll `is_night` and `day` instead of connecting to signals, so they
+// can look the cycle up from their own callbacks without re-binding it
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct DayCycle {
+    base: Base<Node>,
+
+    // Seconds for a whole day and night
+    #[export]
+    #[init(val = 600.0)]
+    day_length: f32,
+
+    // Fraction of the day, 0 is midnight and 0.5 is noon
+    #[export]
+    #[init(val = 0.3)]
+    pub time: f32,
+
+    #[export]
+    #[init(val = 0.25)]
+    dawn: f32,
+
+    #[export]
+    #[init(val = 0.75)]
+    dusk: f32,
+
+    // Fraction of the day daylight takes to fade in or out
+    #[export]
+    #[init(val = 0.05)]
+    twilight: f32,
+
+    #[export]
+    tint: Option<Gd<CanvasModulate>>,
+
+    #[export]
+    #[init(val = Color::WHITE)]
+    day_color: Color,
+
+    #[export]
+    #[init(val = Color::from_rgb(0.15, 0.15, 0.3))]
+    night_color: Color,
+
+    // Days elapsed, goes up at midnight
+    pub day: u32,
+}
+
+#[godot_api]
+impl INode for DayCycle {
+    fn ready(&mut self) {
+        self.base_mut().add_to_group("day_cycle");
+        self.update_tint();
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.time += delta as f32 / self.day_length.max(1.0);
+        if self.time >= 1.0 {
+            self.time -= 1.0;
+            self.day += 1;
+        }
+        self.update_tint();
+    }
+}
+
+impl DayCycle {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<DayCycle>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("day_cycle")?
+            .try_cast::<DayCycle>()
+            .ok()
+    }
+
+    pub fn load_mem(&mut self, mem: &DayMem) {
+        self.day = mem.day;
+        self.time = mem.time.rem_euclid(1.0);
+        self.update_tint();
+    }
+
+    pub fn to_mem(&self) -> DayMem {
+        DayMem {
+            day: self.day,
+            time: self.time,
+        }
+    }
+
+    // Each night is numbered after the day it ends on, so it keeps the same
+    // number across midnight
+    pub fn night(&self) -> u32 {
+        if self.time >= self.dusk {
+            self.day + 1
+        } else {
+            self.day
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.time < self.dawn || self.time >= self.dusk
+    }
+
+    // 1 in full daylight, 0 at night, in between during twilight
+    pub fn daylight(&self) -> f32 {
+        if self.is_night() {
+            return 0.0;
+        }
+        let twilight = self.twilight.max(f32::EPSILON);
+        let since_dawn = (self.time - self.dawn) / twilight;
+        let until_dusk = (self.dusk - self.time) / twilight;
+        since_dawn.min(until_dusk).clamp(0.0, 1.0)
+    }
+
+    fn update_tint(&mut self) {
+        let daylight = self.daylight();
+        let color = self.night_color.lerp(self.day_color, daylight as f64);
+        if let Some(tint) = self.tint.as_mut() {
+            tint.set_color(color);
+        }
+    }
+}
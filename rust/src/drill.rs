@@ -1,24 +1,101 @@
-use godot::prelude::*;
+use godot::{
+    classes::{Input, RayCast2D, TileMapLayer},
+    prelude::*,
+};
+
+use crate::{
+    resource_node::ResourceNode,
+    tile_health::TileHealth,
+    tool::{self, Tool, aim_at_mouse},
+};
 
 #[derive(GodotClass)]
 #[class(init,base=Node2D)]
 pub struct Drill {
     base: Base<Node2D>,
+
+    // Gates which loot table entries this drill can get out of a block
+    #[export]
+    tier: u32,
+
+    #[export]
+    ray: Option<Gd<RayCast2D>>,
+
+    // Seconds between harvests while `tool_use` is held
+    #[export]
+    #[init(val = 0.4)]
+    harvest_interval: f32,
+
+    harvest_timer: f32,
+
+    // Damage per harvest to tiles the drill is pointed at
+    #[export]
+    #[init(val = 1.0)]
+    tile_damage: f32,
 }
 
 #[godot_api]
 impl INode2D for Drill {
     fn ready(&mut self) {
+        if self.ray.is_none() {
+            godot_warn!("Drill must have a ray");
+        }
         self.base_mut().print_tree_pretty();
     }
 
     fn physics_process(&mut self, delta: f64) {
-        let mouse_pos = self.base().get_global_mouse_position();
-        self.base_mut().look_at(mouse_pos);
+        aim_at_mouse(&mut self.base_mut());
+
+        if !Input::singleton().is_action_pressed("tool_use") {
+            // Ready to hit as soon as the button goes down again
+            self.harvest_timer = self.harvest_interval;
+            return;
+        }
+        self.harvest_timer += delta as f32;
+        if self.harvest_timer >= self.harvest_interval {
+            self.harvest_timer = 0.0;
+            self.harvest();
+        }
     }
 }
 
-pub trait Tool {}
+impl Drill {
+    fn harvest(&mut self) {
+        let Some(ray) = self.ray.as_mut() else {
+            return;
+        };
+        ray.force_raycast_update();
+        let Some(collider) = ray.get_collider() else {
+            return;
+        };
+        // Nudged into the tile, the collision point sits on its edge
+        let point = ray.get_collision_point() - ray.get_collision_normal();
+        tool::wear(&self.base());
+        let tier = self.tier();
+        let collider = match collider.try_cast::<ResourceNode>() {
+            Ok(mut node) => {
+                node.bind_mut().harvest(tier);
+                return;
+            }
+            Err(collider) => collider,
+        };
+        if collider.try_cast::<TileMapLayer>().is_err() {
+            return;
+        }
+        let Some(mut tile_health) = TileHealth::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        let cell = tile_health.bind().cell_at(point);
+        if let Some(cell) = cell {
+            let tile_damage = self.tile_damage;
+            tile_health.bind_mut().damage(cell, tile_damage, tier);
+        }
+    }
+}
 
 #[godot_dyn]
-impl Tool for Drill {}
+impl Tool for Drill {
+    fn tier(&self) -> u32 {
+        self.tier
+    }
+}
@@ -0,0 +1,144 @@
+// Seconds spent standing around before wandering off, and the other way around
+const IDLE_TIME: f32 = 2.0;
+const WANDER_TIME: f32 = 3.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Behavior {
+    Idle,
+    Wander,
+    Chase,
+    Attack,
+    Flee,
+}
+
+// What the enemy knows about the world this frame
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Senses {
+    pub sees_player: bool,
+    // Somewhere to head for even without seeing the player, like during a horde
+    pub has_goal: bool,
+    pub player_distance: f32,
+    pub health_fraction: f32,
+    pub attack_range: f32,
+    pub flee_below: f32,
+}
+
+impl Behavior {
+    // Pure so the AI can be checked without a scene
+    pub fn next(self, senses: &Senses, time_in_state: f32) -> Behavior {
+        if senses.health_fraction < senses.flee_below {
+            return Behavior::Flee;
+        }
+        if senses.sees_player {
+            return if senses.player_distance <= senses.attack_range {
+                Behavior::Attack
+            } else {
+                Behavior::Chase
+            };
+        }
+        if senses.has_goal {
+            return Behavior::Chase;
+        }
+        match self {
+            Behavior::Idle if time_in_state >= IDLE_TIME => Behavior::Wander,
+            Behavior::Wander if time_in_state >= WANDER_TIME => Behavior::Idle,
+            Behavior::Idle | Behavior::Wander => self,
+            // Lost sight of the player (or healed up), calm down again
+            Behavior::Chase | Behavior::Attack | Behavior::Flee => Behavior::Idle,
+        }
+    }
+
+    pub fn animation(self) -> &'static str {
+        match self {
+            Behavior::Idle => "idle",
+            Behavior::Wander | Behavior::Chase | Behavior::Flee => "move",
+            Behavior::Attack => "attack",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn senses() -> Senses {
+        Senses {
+            player_distance: f32::INFINITY,
+            health_fraction: 1.0,
+            attack_range: 24.0,
+            flee_below: 0.2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn idles_and_wanders_in_turns() {
+        let senses = senses();
+        assert_eq!(Behavior::Idle.next(&senses, 0.0), Behavior::Idle);
+        assert_eq!(Behavior::Idle.next(&senses, IDLE_TIME), Behavior::Wander);
+        assert_eq!(Behavior::Wander.next(&senses, 0.0), Behavior::Wander);
+        assert_eq!(Behavior::Wander.next(&senses, WANDER_TIME), Behavior::Idle);
+    }
+
+    #[test]
+    fn chases_a_seen_player_and_attacks_in_range() {
+        let far = Senses {
+            sees_player: true,
+            player_distance: 100.0,
+            ..senses()
+        };
+        assert_eq!(Behavior::Idle.next(&far, 0.0), Behavior::Chase);
+        assert_eq!(Behavior::Wander.next(&far, 0.0), Behavior::Chase);
+        let close = Senses {
+            player_distance: 10.0,
+            ..far
+        };
+        assert_eq!(Behavior::Chase.next(&close, 0.0), Behavior::Attack);
+        assert_eq!(Behavior::Attack.next(&far, 0.0), Behavior::Chase);
+    }
+
+    #[test]
+    fn calms_down_after_losing_sight() {
+        let senses = senses();
+        assert_eq!(Behavior::Chase.next(&senses, 0.0), Behavior::Idle);
+        assert_eq!(Behavior::Attack.next(&senses, 0.0), Behavior::Idle);
+    }
+
+    #[test]
+    fn flees_when_hurt_even_in_range() {
+        let hurt = Senses {
+            sees_player: true,
+            player_distance: 10.0,
+            health_fraction: 0.1,
+            ..senses()
+        };
+        assert_eq!(Behavior::Attack.next(&hurt, 0.0), Behavior::Flee);
+        assert_eq!(Behavior::Idle.next(&hurt, 0.0), Behavior::Flee);
+        let healed = Senses {
+            health_fraction: 0.5,
+            sees_player: false,
+            ..hurt
+        };
+        assert_eq!(Behavior::Flee.next(&healed, 0.0), Behavior::Idle);
+    }
+
+    #[test]
+    fn never_flees_when_flee_below_is_zero() {
+        let hurt = Senses {
+            health_fraction: 0.01,
+            flee_below: 0.0,
+            ..senses()
+        };
+        assert_eq!(Behavior::Idle.next(&hurt, 0.0), Behavior::Idle);
+    }
+
+    #[test]
+    fn heads_for_a_goal_without_seeing_the_player() {
+        let goal = Senses {
+            has_goal: true,
+            ..senses()
+        };
+        assert_eq!(Behavior::Idle.next(&goal, 0.0), Behavior::Chase);
+        assert_eq!(Behavior::Wander.next(&goal, 0.0), Behavior::Chase);
+    }
+}
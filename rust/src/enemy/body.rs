@@ -0,0 +1,497 @@
+use godot::{
+    classes::{
+        AnimationPlayer, CharacterBody2D, ICharacterBody2D, PhysicsRayQueryParameters2D,
+        RandomNumberGenerator,
+    },
+    prelude::*,
+};
+
+use crate::{
+    enemy::{
+        ai::{Behavior, Senses},
+        kind::EnemyType,
+    },
+    health::{DamageType, Health},
+    knockback::{self, Knockback},
+    map::{EntitiesMem, Vector2Mem},
+    movement::approach,
+    pathfinding::{grid::Action, pathfinder::Pathfinder},
+    pickable_manager::PickableManager,
+    stats::Stats,
+    status::effects::StatusEffects,
+};
+
+// Seconds between path queries while chasing
+const REPATH_TIME: f32 = 0.5;
+// Pixels from a waypoint before moving on to the next one
+const WAYPOINT_REACHED: f32 = 6.0;
+
+#[derive(GodotClass)]
+#[class(init, base=CharacterBody2D)]
+pub struct Enemy {
+    base: Base<CharacterBody2D>,
+
+    #[export]
+    kind: Option<Gd<EnemyType>>,
+
+    #[export]
+    health: Option<Gd<Health>>,
+
+    #[export]
+    status_effects: Option<Gd<StatusEffects>>,
+
+    #[export]
+    anim_player: Option<Gd<AnimationPlayer>>,
+
+    #[export]
+    flipper: Option<Gd<Node2D>>,
+
+    #[init(val = Behavior::Idle)]
+    behavior: Behavior,
+
+    behavior_time: f32,
+
+    // -1 or 1 while wandering
+    #[init(val = 1.0)]
+    wander_dir: f32,
+
+    attack_timer: f32,
+
+    // Seconds left before a knockback gives control back
+    stun_timer: f32,
+
+    // Waypoints toward the player, the first one is the next to reach
+    path: Vec<(Vector2, Action)>,
+
+    repath_timer: f32,
+
+    sees_player: bool,
+
+    // Chased whenever the player isn't in sight
+    goal: Option<Gd<Node2D>>,
+
+    // Health from a save, applied once the Health child is ready
+    restored_health: Option<(f32, f32)>,
+
+    // Tier of the tool that last hit it, gates the loot it drops
+    last_hit_tier: u32,
+
+    // Set once loot has dropped, it stays in the tree until the frame ends
+    dead: bool,
+
+    #[init(val = RandomNumberGenerator::new_gd())]
+    rng: Gd<RandomNumberGenerator>,
+}
+
+#[godot_api]
+impl ICharacterBody2D for Enemy {
+    fn ready(&mut self) {
+        if self.kind.is_none() {
+            godot_error!("Enemy must have a kind");
+            self.base_mut().set_physics_process(false);
+            self.base_mut().queue_free();
+            return;
+        }
+        if self.anim_player.is_none() {
+            godot_warn!("Enemy AnimationPlayer node not found");
+        }
+        if self.health.is_none() {
+            let health = Health::new_alloc();
+            self.base_mut().add_child(&health);
+            self.health = Some(health);
+        }
+        if self.status_effects.is_none() {
+            let status_effects = StatusEffects::new_alloc();
+            self.base_mut().add_child(&status_effects);
+            self.status_effects = Some(status_effects);
+        }
+        let max_health = self.kind().bind().max_health;
+        let (current, max) = self
+            .restored_health
+            .take()
+            .unwrap_or((max_health, max_health));
+        {
+            let mut health = self.health_mut().bind_mut();
+            health.max_health = max;
+            health.apply_health(current);
+        }
+        self.rng.randomize();
+        self.base_mut().add_to_group("enemy");
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let delta = delta as f32;
+        if self.dead {
+            return;
+        }
+        if self.health().bind().is_dead() {
+            self.die();
+            return;
+        }
+        self.attack_timer = (self.attack_timer - delta).max(0.0);
+        self.stun_timer = (self.stun_timer - delta).max(0.0);
+        self.think(delta);
+        self.update_path(delta);
+        self.steer(delta);
+        self.base_mut().move_and_slide();
+        self.contact_damage();
+    }
+}
+
+impl Enemy {
+    pub fn from_mem(mem: &EntitiesMem) -> Option<Gd<Enemy>> {
+        let scene = try_load::<PackedScene>(mem.scene.as_str()).ok()?;
+        let mut enemy = scene.instantiate()?.try_cast::<Enemy>().ok()?;
+        enemy.bind_mut().restored_health = Some((mem.health as f32, mem.max_health as f32));
+        enemy.set_global_position(Vector2::new(mem.pos.x, mem.pos.y));
+        Some(enemy)
+    }
+
+    pub fn to_mem(&self) -> EntitiesMem {
+        let pos = self.base().get_global_position();
+        let health = self.health().bind();
+        EntitiesMem {
+            pos: Vector2Mem { x: pos.x, y: pos.y },
+            scene: self.base().get_scene_file_path().to_string(),
+            health: health.health().round().clamp(0.0, u8::MAX as f32) as u8,
+            max_health: health.max_health.round().clamp(0.0, u8::MAX as f32) as u8,
+        }
+    }
+
+    // Called by weapons for every hit that lands
+    pub fn hit_with(&mut self, tool_tier: u32) {
+        self.last_hit_tier = tool_tier;
+    }
+
+    pub fn set_goal(&mut self, goal: Option<Gd<Node2D>>) {
+        self.goal = goal;
+    }
+
+    pub fn has_goal(&self) -> bool {
+        self.goal.is_some()
+    }
+
+    // The player once in sight, the goal otherwise
+    fn chase_target(&self) -> Option<Vector2> {
+        let goal = self
+            .goal
+            .as_ref()
+            .filter(|goal| !self.sees_player && goal.is_instance_valid());
+        match goal {
+            Some(goal) => Some(goal.get_global_position()),
+            None => self.player().map(|player| player.get_global_position()),
+        }
+    }
+
+    fn player(&self) -> Option<Gd<Node2D>> {
+        let mut tree = self.base().get_tree()?;
+        tree.get_first_node_in_group("player")?
+            .try_cast::<Node2D>()
+            .ok()
+    }
+
+    fn think(&mut self, delta: f32) {
+        let player = self.player();
+        let (sight_range, attack_range, flee_below) = {
+            let kind = self.kind().bind();
+            (kind.sight_range, kind.attack_range, kind.flee_below)
+        };
+        let position = self.base().get_global_position();
+        let player_distance = player.as_ref().map_or(f32::INFINITY, |player| {
+            player.get_global_position().distance_to(position)
+        });
+        let sees_player = player_distance <= sight_range
+            && player
+                .as_ref()
+                .is_some_and(|player| self.line_of_sight(player));
+        let health_fraction = {
+            let health = self.health().bind();
+            health.health() / health.max_health.max(1.0)
+        };
+        self.sees_player = sees_player;
+        let has_goal = self
+            .goal
+            .as_ref()
+            .is_some_and(|goal| goal.is_instance_valid());
+        let senses = Senses {
+            sees_player,
+            has_goal,
+            player_distance,
+            health_fraction,
+            attack_range,
+            flee_below,
+        };
+
+        self.behavior_time += delta;
+        let next = self.behavior.next(&senses, self.behavior_time);
+        if next != self.behavior {
+            self.behavior = next;
+            self.behavior_time = 0.0;
+            if next == Behavior::Wander {
+                self.wander_dir = if self.rng.randf() < 0.5 { -1.0 } else { 1.0 };
+            }
+            let anim = next.animation();
+            if let Some(anim_player) = self.anim_player.as_mut()
+                && anim_player.has_animation(anim)
+            {
+                anim_player.set_current_animation(anim);
+            }
+        }
+        if self.behavior == Behavior::Attack
+            && let Some(player) = player
+        {
+            self.attack(&player);
+        }
+    }
+
+    fn line_of_sight(&self, player: &Gd<Node2D>) -> bool {
+        let Some(mut world) = self.base().get_world_2d() else {
+            return false;
+        };
+        let Some(mut space) = world.get_direct_space_state() else {
+            return false;
+        };
+        let from = self.base().get_global_position();
+        let to = player.get_global_position();
+        let Some(mut query) = PhysicsRayQueryParameters2D::create(from, to) else {
+            return false;
+        };
+        let rid = self.base().get_rid();
+        query.set_exclude(&array![rid]);
+        let hit = space.intersect_ray(&query);
+        hit.get("collider")
+            .and_then(|collider| collider.try_to::<Gd<Node>>().ok())
+            .is_some_and(|collider| collider.is_in_group("player"))
+    }
+
+    fn update_path(&mut self, delta: f32) {
+        if self.behavior != Behavior::Chase {
+            self.path.clear();
+            self.repath_timer = 0.0;
+            return;
+        }
+        self.repath_timer -= delta;
+        if self.repath_timer > 0.0 {
+            return;
+        }
+        self.repath_timer = REPATH_TIME;
+        let Some(profile) = self.kind().bind().movement_profile.clone() else {
+            return;
+        };
+        let (Some(target), Some(pathfinder)) = (
+            self.chase_target(),
+            Pathfinder::find(&self.to_gd().upcast()),
+        ) else {
+            return;
+        };
+        let position = self.base().get_global_position();
+        self.path = pathfinder
+            .bind()
+            .find_path(position, target, &profile.bind())
+            .unwrap_or_default();
+    }
+
+    // Drops the waypoints already reached and returns the next one
+    fn next_waypoint(&mut self) -> Option<(Vector2, Action)> {
+        let position = self.base().get_global_position();
+        while let Some(&(point, action)) = self.path.first() {
+            let reached = (point.x - position.x).abs() <= WAYPOINT_REACHED
+                && (action != Action::Climb || (point.y - position.y).abs() <= WAYPOINT_REACHED);
+            if !reached {
+                return Some((point, action));
+            }
+            self.path.remove(0);
+        }
+        None
+    }
+
+    // Takes over the velocity until the stun wears off
+    pub fn knockback(&mut self, knockback: Knockback) {
+        self.stun_timer = self.stun_timer.max(knockback.stun);
+        self.base_mut().set_velocity(knockback.velocity());
+    }
+
+    fn steer(&mut self, delta: f32) {
+        if self.stun_timer > 0.0 {
+            let mut velocity = self.base().get_velocity();
+            if !self.base().is_on_floor() {
+                velocity += self.base().get_gravity() * delta;
+            }
+            self.base_mut().set_velocity(velocity);
+            return;
+        }
+        let (wander_speed, chase_speed, acceleration, jump_velocity) = {
+            let kind = self.kind().bind();
+            (
+                kind.wander_speed,
+                kind.chase_speed,
+                kind.acceleration,
+                kind.jump_velocity,
+            )
+        };
+        // Slows and the like from status effects
+        let speed_multiplier = self.stats().speed_multiplier;
+        let (wander_speed, chase_speed) = (
+            wander_speed * speed_multiplier,
+            chase_speed * speed_multiplier,
+        );
+        let position = self.base().get_global_position();
+        let toward_player = self.player().map_or(0.0, |player| {
+            (player.get_global_position().x - position.x).signum()
+        });
+        let toward_target = self
+            .chase_target()
+            .map_or(0.0, |target| (target.x - position.x).signum());
+        let waypoint = match self.behavior {
+            Behavior::Chase => self.next_waypoint(),
+            _ => None,
+        };
+        let target = match (self.behavior, waypoint) {
+            (Behavior::Idle | Behavior::Attack, _) => 0.0,
+            (Behavior::Wander, _) => self.wander_dir * wander_speed,
+            (Behavior::Chase, Some((point, _))) => (point.x - position.x).signum() * chase_speed,
+            (Behavior::Chase, None) => toward_target * chase_speed,
+            (Behavior::Flee, _) => -toward_player * chase_speed,
+        };
+
+        let mut velocity = self.base().get_velocity();
+        velocity.x = approach(velocity.x, target, acceleration, delta);
+        let on_floor = self.base().is_on_floor();
+        if let Some((point, Action::Climb)) = waypoint {
+            velocity.y = (point.y - position.y).signum() * wander_speed;
+        } else if !on_floor {
+            velocity += self.base().get_gravity() * delta;
+        } else if matches!(waypoint, Some((_, Action::Jump))) {
+            velocity.y = -jump_velocity;
+        } else if target != 0.0 && self.base().is_on_wall() {
+            match self.behavior {
+                // Turn around at walls while wandering, hop over them otherwise
+                Behavior::Wander => self.wander_dir = -self.wander_dir,
+                _ => velocity.y = -jump_velocity,
+            }
+        }
+        self.base_mut().set_velocity(velocity);
+
+        if target != 0.0
+            && let Some(flipper) = self.flipper.as_mut()
+        {
+            flipper.set_scale(Vector2::new(target.signum(), 1.0));
+        }
+    }
+
+    fn attack(&mut self, player: &Gd<Node2D>) {
+        if self.attack_timer > 0.0 {
+            return;
+        }
+        let (cooldown, damage) = {
+            let kind = self.kind().bind();
+            (kind.attack_cooldown, kind.attack_damage)
+        };
+        self.attack_timer = cooldown;
+        if self.hit(player, damage) {
+            self.bite(player);
+        }
+    }
+
+    fn bite(&mut self, target: &Gd<Node2D>) {
+        let (effect, chance) = {
+            let kind = self.kind().bind();
+            (kind.bite_effect.clone(), kind.bite_chance)
+        };
+        let Some(effect) = effect else {
+            return;
+        };
+        if self.rng.randf() > chance {
+            return;
+        }
+        if let Some(mut status_effects) = StatusEffects::of(&target.clone().upcast()) {
+            status_effects.bind_mut().apply(effect);
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        if let Some(status_effects) = self.status_effects.as_ref() {
+            status_effects.bind().apply_modifiers(&mut stats);
+        }
+        stats
+    }
+
+    fn contact_damage(&mut self) {
+        let damage = self.kind().bind().contact_damage;
+        if damage <= 0.0 {
+            return;
+        }
+        for i in 0..self.base().get_slide_collision_count() {
+            let Some(collision) = self.base_mut().get_slide_collision(i) else {
+                continue;
+            };
+            let Some(collider) = collision.get_collider() else {
+                continue;
+            };
+            let Ok(body) = collider.try_cast::<Node2D>() else {
+                continue;
+            };
+            if body.is_in_group("player") {
+                self.hit(&body, damage);
+            }
+        }
+    }
+
+    // Returns true if the hit landed
+    fn hit(&mut self, target: &Gd<Node2D>, damage: f32) -> bool {
+        let Some(mut health) = Health::of(&target.clone().upcast()) else {
+            return false;
+        };
+        if !health.bind_mut().damage(damage, DamageType::Physical) {
+            return false;
+        }
+        let (strength, stun) = {
+            let kind = self.kind().bind();
+            (kind.knockback, kind.knockback_stun)
+        };
+        let away = (target.get_global_position() - self.base().get_global_position()).normalized();
+        knockback::apply(
+            target.clone(),
+            Knockback {
+                direction: away,
+                strength,
+                stun,
+            },
+        );
+        true
+    }
+
+    fn die(&mut self) {
+        self.dead = true;
+        let loot_table = self.kind().bind().loot_table.clone();
+        if let Some(loot_table) = loot_table
+            && let Some(mut manager) = PickableManager::find(&self.to_gd().upcast())
+        {
+            let position = self.base().get_global_position();
+            let tool_tier = self.last_hit_tier;
+            manager
+                .bind_mut()
+                .spawn_loot(&loot_table, tool_tier, position);
+        }
+        self.base_mut().queue_free();
+    }
+
+    fn kind(&self) -> &Gd<EnemyType> {
+        self.kind
+            .as_ref()
+            .expect("kind must be initialized in _ready()")
+    }
+
+    fn health(&self) -> &Gd<Health> {
+        self.health
+            .as_ref()
+            .expect("health must be initialized in _ready()")
+    }
+
+    fn health_mut(&mut self) -> &mut Gd<Health> {
+        self.health
+            .as_mut()
+            .expect("health must be initialized in _ready()")
+    }
+}
@@ -0,0 +1,89 @@
+use godot::prelude::*;
+
+use crate::{
+    loot::LootTable, pathfinding::pathfinder::MovementProfile, status::effect::StatusEffect,
+};
+
+// Everything that makes one kind of undead different from another
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct EnemyType {
+    base: Base<Resource>,
+
+    #[export]
+    pub name: StringName,
+
+    #[export]
+    #[init(val = 20.0)]
+    pub max_health: f32,
+
+    #[export]
+    #[init(val = 60.0)]
+    pub wander_speed: f32,
+
+    #[export]
+    #[init(val = 120.0)]
+    pub chase_speed: f32,
+
+    // Pixels per second squared
+    #[export]
+    #[init(val = 800.0)]
+    pub acceleration: f32,
+
+    // Used to hop over one-tile steps while chasing
+    #[export]
+    #[init(val = 250.0)]
+    pub jump_velocity: f32,
+
+    #[export]
+    #[init(val = 300.0)]
+    pub sight_range: f32,
+
+    #[export]
+    #[init(val = 24.0)]
+    pub attack_range: f32,
+
+    #[export]
+    #[init(val = 10.0)]
+    pub attack_damage: f32,
+
+    // Seconds between melee attacks
+    #[export]
+    #[init(val = 1.0)]
+    pub attack_cooldown: f32,
+
+    // Damage for simply bumping into the player
+    #[export]
+    #[init(val = 5.0)]
+    pub contact_damage: f32,
+
+    #[export]
+    #[init(val = 200.0)]
+    pub knockback: f32,
+
+    // Seconds the player can't steer after a hit
+    #[export]
+    #[init(val = 0.2)]
+    pub knockback_stun: f32,
+
+    // Inflicted by melee attacks that land, like an infection from a bite
+    #[export]
+    pub bite_effect: Option<Gd<StatusEffect>>,
+
+    #[export]
+    #[init(val = 1.0)]
+    pub bite_chance: f32,
+
+    // Runs away below this fraction of max health, 0 never flees
+    #[export]
+    #[init(val = 0.2)]
+    pub flee_below: f32,
+
+    #[export]
+    pub loot_table: Option<Gd<LootTable>>,
+
+    // Paths around terrain while chasing, chases in a straight line when unset.
+    // The jump height should match what jump_velocity can actually reach.
+    #[export]
+    pub movement_profile: Option<Gd<MovementProfile>>,
+}
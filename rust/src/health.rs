@@ -0,0 +1,167 @@
+use godot::prelude::*;
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fall,
+    Fire,
+    Poison,
+    Infection,
+    Explosion,
+}
+
+impl DamageType {
+    // Armor only helps against things that actually hit you
+    fn armor_applies(self) -> bool {
+        matches!(self, DamageType::Physical | DamageType::Explosion)
+    }
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct Health {
+    base: Base<Node>,
+
+    #[export]
+    #[init(val = 100.0)]
+    pub max_health: f32,
+
+    #[var]
+    #[init(val = 100.0)]
+    health: f32,
+
+    // Every point of armor is 1% less physical damage, with diminishing returns
+    #[var]
+    pub armor: f32,
+
+    // Seconds after a hit during which further hits are ignored
+    #[export]
+    #[init(val = 0.5)]
+    invulnerability_time: f32,
+
+    // Health per second, starts `regeneration_delay` seconds after the last hit
+    #[export]
+    regeneration: f32,
+
+    #[export]
+    #[init(val = 3.0)]
+    regeneration_delay: f32,
+
+    invulnerable_timer: f32,
+
+    since_damage: f32,
+
+    dead: bool,
+}
+
+#[godot_api]
+impl Health {
+    // `damage_type` is a DamageType, sent as its i64 value
+    #[signal]
+    pub fn damaged(amount: f32, damage_type: i64);
+
+    #[signal]
+    pub fn health_changed(health: f32, max_health: f32);
+
+    #[signal]
+    pub fn died();
+}
+
+#[godot_api]
+impl INode for Health {
+    fn ready(&mut self) {
+        self.health = self.max_health;
+    }
+
+    fn process(&mut self, delta: f64) {
+        let delta = delta as f32;
+        self.invulnerable_timer = (self.invulnerable_timer - delta).max(0.0);
+        self.since_damage += delta;
+        if self.dead
+            || self.regeneration <= 0.0
+            || self.since_damage < self.regeneration_delay
+            || self.health >= self.max_health
+        {
+            return;
+        }
+        self.apply_health(self.health + self.regeneration * delta);
+    }
+}
+
+impl Health {
+    // Finds the Health component among the node's children
+    pub fn of(node: &Gd<Node>) -> Option<Gd<Health>> {
+        node.get_children()
+            .iter_shared()
+            .find_map(|child| child.try_cast::<Health>().ok())
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.dead
+    }
+
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_timer > 0.0
+    }
+
+    // Returns false when the hit was ignored (dead or invulnerable), hitters
+    // only knock back on hits that land
+    pub fn damage(&mut self, amount: f32, damage_type: DamageType) -> bool {
+        if self.dead || self.is_invulnerable() || amount <= 0.0 {
+            return false;
+        }
+        let amount = if damage_type.armor_applies() {
+            amount * 100.0 / (100.0 + self.armor.max(0.0))
+        } else {
+            amount
+        };
+        self.invulnerable_timer = self.invulnerability_time;
+        self.since_damage = 0.0;
+        self.signals().damaged().emit(amount, damage_type as i64);
+        self.apply_health(self.health - amount);
+        if self.health <= 0.0 {
+            self.dead = true;
+            self.signals().died().emit();
+        }
+        true
+    }
+
+    // Damage over time from status effects, goes through invulnerability
+    // without granting any, and doesn't hold off regeneration
+    pub fn tick_damage(&mut self, amount: f32, damage_type: DamageType) {
+        if self.dead || amount <= 0.0 {
+            return;
+        }
+        self.signals().damaged().emit(amount, damage_type as i64);
+        self.apply_health(self.health - amount);
+        if self.health <= 0.0 {
+            self.dead = true;
+            self.signals().died().emit();
+        }
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        if self.dead {
+            return;
+        }
+        self.apply_health(self.health + amount);
+    }
+
+    pub fn revive(&mut self) {
+        self.dead = false;
+        self.invulnerable_timer = self.invulnerability_time;
+        self.apply_health(self.max_health);
+    }
+
+    pub fn apply_health(&mut self, health: f32) {
+        self.health = health.clamp(0.0, self.max_health);
+        let (health, max_health) = (self.health, self.max_health);
+        self.signals().health_changed().emit(health, max_health);
+    }
+}
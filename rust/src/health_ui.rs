@@ -0,0 +1,96 @@
+use godot::{
+    classes::{Container, Control, IControl, ProgressBar, Texture2D, TextureRect},
+    prelude::*,
+};
+
+use crate::health::Health;
+
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct HealthUI {
+    base: Base<Control>,
+
+    #[export]
+    bar: Option<Gd<ProgressBar>>,
+
+    // Optional row of hearts next to the bar
+    #[export]
+    hearts: Option<Gd<Container>>,
+
+    #[export]
+    heart_full: Option<Gd<Texture2D>>,
+
+    #[export]
+    heart_empty: Option<Gd<Texture2D>>,
+
+    #[export]
+    #[init(val = 20.0)]
+    health_per_heart: f32,
+
+    #[var]
+    pub health: Option<Gd<Health>>,
+}
+
+#[godot_api]
+impl IControl for HealthUI {
+    fn ready(&mut self) {
+        if self.bar.is_none() && self.hearts.is_none() {
+            godot_warn!("HealthUI: neither bar nor hearts are set");
+        }
+        let Some(health) = self.health.clone() else {
+            godot_warn!("HealthUI: health is not set");
+            return;
+        };
+        health
+            .signals()
+            .health_changed()
+            .connect_other(self, Self::on_health_changed);
+        let (current, max) = {
+            let health = health.bind();
+            (health.health(), health.max_health)
+        };
+        self.on_health_changed(current, max);
+    }
+}
+
+impl HealthUI {
+    fn on_health_changed(&mut self, health: f32, max_health: f32) {
+        if let Some(bar) = self.bar.as_mut() {
+            bar.set_max(max_health as f64);
+            bar.set_value(health as f64);
+        }
+        self.refresh_hearts(health, max_health);
+    }
+
+    fn refresh_hearts(&mut self, health: f32, max_health: f32) {
+        let per_heart = self.health_per_heart.max(1.0);
+        let full = self.heart_full.clone();
+        let empty = self.heart_empty.clone();
+        let Some(hearts) = self.hearts.as_mut() else {
+            return;
+        };
+        let count = (max_health / per_heart).ceil() as i32;
+        while hearts.get_child_count() > count {
+            if let Some(mut heart) = hearts.get_child(hearts.get_child_count() - 1) {
+                hearts.remove_child(&heart);
+                heart.queue_free();
+            }
+        }
+        while hearts.get_child_count() < count {
+            hearts.add_child(&TextureRect::new_alloc());
+        }
+        let filled = (health / per_heart).ceil() as i32;
+        for i in 0..count {
+            let Some(heart) = hearts.get_child(i) else {
+                continue;
+            };
+            let Ok(mut heart) = heart.try_cast::<TextureRect>() else {
+                continue;
+            };
+            let texture = if i < filled { &full } else { &empty };
+            if let Some(texture) = texture {
+                heart.set_texture(texture);
+            }
+        }
+    }
+}
@@ -1,6 +1,18 @@
 use godot::prelude::*;
 
-use crate::inventory::{item::InventoryItem, slot::InventorySlot};
+use crate::{
+    inventory::{item::InventoryItem, slot::InventorySlot},
+    stats::Stats,
+};
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum SortMode {
+    #[default]
+    Id,
+    Category,
+    Quantity,
+}
 
 #[derive(GodotClass)]
 #[class(init, base=Resource)]
@@ -15,6 +27,10 @@ pub struct Inventory {
     #[init(val = 3)]
     pub hotbar_size: u32,
 
+    #[export]
+    #[init(val = 5)]
+    pub row_size: u32,
+
     #[export]
     slots: Array<Gd<InventorySlot>>,
 }
@@ -27,46 +43,259 @@ impl Inventory {
         }
         self.size = size;
         let new_size = (self.hotbar_size + self.size) as usize;
-        let new_slot = InventorySlot::new_gd();
-        self.slots.resize(new_size, &new_slot);
+        // Every slot needs its own resource, `resize` would share a single one
+        while self.slots.len() < new_size {
+            self.slots.push(&InventorySlot::new_gd());
+        }
         Ok(())
     }
 
+    // Drops the trailing slots. Their items are moved into the remaining slots first,
+    // whatever still doesn't fit is either refused or handed back to the caller to spill.
+    #[allow(dead_code)]
+    pub fn shrink(&mut self, size: u32, spill: bool) -> Result<Vec<Stack>, ()> {
+        if size >= self.size {
+            return Err(());
+        }
+        let snapshot: Vec<(Option<Gd<InventoryItem>>, u32, u32)> = self
+            .slots
+            .iter_shared()
+            .map(|slot| {
+                let slot = slot.bind();
+                (slot.item.clone(), slot.quantity, slot.durability)
+            })
+            .collect();
+        let old_size = self.size;
+        let new_len = (self.hotbar_size + size) as usize;
+        let removed: Vec<Gd<InventorySlot>> = self.slots.iter_shared().skip(new_len).collect();
+        let lost: Vec<Stack> = removed
+            .iter()
+            .filter_map(|slot| {
+                let slot = slot.bind();
+                let item = slot.item.clone()?;
+                (slot.quantity > 0).then_some(Stack {
+                    item,
+                    quantity: slot.quantity,
+                    durability: slot.durability,
+                })
+            })
+            .collect();
+
+        while self.slots.len() > new_len {
+            self.slots.pop();
+        }
+        self.size = size;
+        let mut spilled = Vec::new();
+        for stack in lost {
+            if let Err(left) = self.add_item(&stack.item, stack.quantity, stack.durability) {
+                spilled.push(Stack {
+                    quantity: left,
+                    ..stack
+                });
+            }
+        }
+        if spilled.is_empty() || spill {
+            return Ok(spilled);
+        }
+
+        // Refused: put the slots and their contents back as they were
+        for slot in removed.iter() {
+            self.slots.push(slot);
+        }
+        self.size = old_size;
+        for (mut slot, (item, quantity, durability)) in self.slots.iter_shared().zip(snapshot) {
+            let mut slot = slot.bind_mut();
+            slot.item = item;
+            slot.quantity = quantity;
+            slot.durability = durability;
+        }
+        Err(())
+    }
+
+    // Builds an inventory where every slot only accepts items with the given tag
+    pub fn with_filters(tags: &[&str]) -> Gd<Self> {
+        let mut inventory = Self::new_gd();
+        {
+            let mut inv = inventory.bind_mut();
+            inv.size = tags.len() as u32;
+            inv.hotbar_size = 0;
+            for tag in tags {
+                let mut slot = InventorySlot::new_gd();
+                slot.bind_mut().accepted_tags.push(&StringName::from(*tag));
+                inv.slots.push(&slot);
+            }
+        }
+        inventory
+    }
+
     #[allow(dead_code)]
-    pub fn remove_from_slot(&mut self, slot: u32 /*TODO: , quantity: u32*/) -> Result<(), ()> {
+    pub fn remove_from_slot(&mut self, slot: u32, quantity: u32) -> Result<(), ()> {
         let Some(mut slot) = self.slots.get(slot as usize) else {
             return Err(());
         };
 
         let mut slot = slot.bind_mut();
-        slot.item = None;
-        slot.quantity = 0;
+        if slot.item.is_none() || slot.quantity < quantity {
+            return Err(());
+        }
+        slot.quantity -= quantity;
+        if slot.quantity == 0 {
+            slot.item = None;
+        }
 
         Ok(())
     }
 
+    // Takes `quantity` of the item out of whichever slots hold it, nothing is
+    // taken unless there's enough of it
+    pub fn take(&mut self, item: &Gd<InventoryItem>, mut quantity: u32) -> Result<(), ()> {
+        let id = item.bind().id();
+        let holding: Vec<u32> = self
+            .slots
+            .iter_shared()
+            .enumerate()
+            .filter(|(_, slot)| {
+                slot.bind()
+                    .item
+                    .as_ref()
+                    .is_some_and(|item| item.bind().id() == id)
+            })
+            .map(|(idx, _)| idx as u32)
+            .collect();
+        let available: u32 = holding
+            .iter()
+            .filter_map(|&idx| self.slots.get(idx as usize))
+            .map(|slot| slot.bind().quantity)
+            .sum();
+        if available < quantity {
+            return Err(());
+        }
+        for idx in holding {
+            let Some(held) = self
+                .slots
+                .get(idx as usize)
+                .map(|slot| slot.bind().quantity)
+            else {
+                continue;
+            };
+            let taken = held.min(quantity);
+            self.remove_from_slot(idx, taken)?;
+            quantity -= taken;
+            if quantity == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Takes one consumable out of the slot and hands it back so its effect can be applied
+    pub fn consume(&mut self, slot: u32) -> Result<Gd<InventoryItem>, ()> {
+        let Some(item) = self
+            .slots
+            .get(slot as usize)
+            .and_then(|slot| slot.bind().item.clone())
+        else {
+            return Err(());
+        };
+        if !item.bind().is_consumable() {
+            return Err(());
+        }
+        self.remove_from_slot(slot, 1)?;
+        Ok(item)
+    }
+
+    // Whether at least one more of the item would fit somewhere
+    pub fn can_fit(&self, item: &Gd<InventoryItem>) -> bool {
+        let item_obj = item.bind();
+        self.slots.iter_shared().any(|slot| {
+            let slot = slot.bind();
+            match slot.item.as_ref() {
+                Some(existing) => {
+                    existing.bind().id() == item_obj.id() && slot.quantity < item_obj.max_stack
+                }
+                None => slot.accepts(item),
+            }
+        })
+    }
+
+    // Empties every slot, handing back what was in them
+    pub fn take_all(&mut self) -> Vec<Stack> {
+        let mut taken = Vec::new();
+        for mut slot in self.slots.iter_shared() {
+            let mut slot = slot.bind_mut();
+            if let Some(item) = slot.item.take()
+                && slot.quantity > 0
+            {
+                taken.push(Stack {
+                    item,
+                    quantity: slot.quantity,
+                    durability: slot.durability,
+                });
+            }
+            slot.quantity = 0;
+            slot.durability = 0;
+        }
+        taken
+    }
+
+    // Prefers an empty slot, otherwise the first occupied one that would take the item
+    pub fn find_slot_for(&self, item: &Gd<InventoryItem>) -> Option<u32> {
+        let mut occupied = None;
+        for (idx, slot) in self.slots.iter_shared().enumerate() {
+            let slot = slot.bind();
+            if !slot.accepts(item) {
+                continue;
+            }
+            if slot.item.is_none() {
+                return Some(idx as u32);
+            }
+            occupied.get_or_insert(idx as u32);
+        }
+        occupied
+    }
+
+    pub fn total_modifiers(&self) -> Stats {
+        let mut stats = Stats::default();
+        for slot in self.slots.iter_shared() {
+            let slot = slot.bind();
+            let Some(item) = slot.item.as_ref() else {
+                continue;
+            };
+            if let Some(modifiers) = item.bind().modifiers.as_ref() {
+                stats.apply(&modifiers.bind());
+            }
+        }
+        stats
+    }
+
+    // `durability` is what the stack has left, 0 for a new one
     #[allow(dead_code)]
-    pub fn add_item(&mut self, item: &Gd<InventoryItem>, mut quantity: u32) -> Result<(), u32> {
+    pub fn add_item(
+        &mut self,
+        item: &Gd<InventoryItem>,
+        mut quantity: u32,
+        durability: u32,
+    ) -> Result<(), u32> {
         let item_obj = item.bind();
         let max_stack = item_obj.get_max_stack();
 
-        // First, try to fill existing stacks of the same item
+        // First, try to fill existing stacks of the same item. Items that wear
+        // out keep to their own slots, like in `collect_stacks`
         for mut slot in self.slots.iter_shared() {
-            if quantity == 0 {
+            if quantity == 0 || item_obj.max_durability > 0 {
                 break;
             }
 
             let mut slot = slot.bind_mut();
 
-            if let Some(existing_item) = &slot.item {
-                if existing_item.get_name().to_string() == item_obj.get_name().to_string()
-                    && slot.quantity < max_stack
-                {
-                    let available_space = max_stack - slot.quantity;
-                    let to_add = available_space.min(quantity);
-                    slot.quantity += to_add;
-                    quantity -= to_add;
-                }
+            if let Some(existing_item) = &slot.item
+                && existing_item.get_name().to_string() == item_obj.get_name().to_string()
+                && slot.quantity < max_stack
+            {
+                let available_space = max_stack - slot.quantity;
+                let to_add = available_space.min(quantity);
+                slot.quantity += to_add;
+                quantity -= to_add;
             }
         }
 
@@ -78,10 +307,11 @@ impl Inventory {
 
             let mut slot = slot.bind_mut();
 
-            if slot.item.is_none() {
+            if slot.item.is_none() && slot.accepts(item) {
                 let to_add = max_stack.min(quantity);
                 slot.item = Some(item.clone());
                 slot.quantity = to_add;
+                slot.durability = item_obj.durability_or_new(durability);
                 quantity -= to_add;
             }
         }
@@ -99,9 +329,122 @@ impl Inventory {
         let mut from = from_slot.bind_mut();
         let mut to = to_slot.bind_mut();
 
+        if from.item.as_ref().is_some_and(|item| !to.accepts(item))
+            || to.item.as_ref().is_some_and(|item| !from.accepts(item))
+        {
+            return Err(());
+        }
+
         std::mem::swap(&mut from.item, &mut to.item);
         std::mem::swap(&mut from.quantity, &mut to.quantity);
+        std::mem::swap(&mut from.durability, &mut to.durability);
 
         Ok(())
     }
+
+    #[allow(dead_code)]
+    pub fn compact(&mut self) {
+        let stacks = self.collect_stacks();
+        self.write_stacks(stacks);
+    }
+
+    #[allow(dead_code)]
+    pub fn sort(&mut self, mode: SortMode) {
+        let mut stacks = self.collect_stacks();
+        match mode {
+            SortMode::Id => stacks.sort_by_key(|stack| stack.id()),
+            SortMode::Category => {
+                stacks.sort_by_key(|stack| (stack.item.bind().category, stack.id()))
+            }
+            // Biggest stacks first, ties broken by id so the order is stable
+            SortMode::Quantity => stacks.sort_by(|a, b| {
+                b.quantity
+                    .cmp(&a.quantity)
+                    .then_with(|| a.id().cmp(&b.id()))
+            }),
+        }
+        self.write_stacks(stacks);
+    }
+
+    // Merges every non-hotbar slot into full stacks, keeping first-seen order.
+    // Filtered slots (ammo, fuel, ...) are left where they are, and items that
+    // wear out are never merged so each keeps its own durability.
+    fn collect_stacks(&self) -> Vec<Stack> {
+        let mut totals: Vec<Stack> = Vec::new();
+        for slot in self.slots.iter_shared().skip(self.hotbar_size as usize) {
+            let slot = slot.bind();
+            if slot.is_filtered() {
+                continue;
+            }
+            let Some(item) = slot.item.as_ref() else {
+                continue;
+            };
+            if slot.quantity == 0 {
+                continue;
+            }
+            let stack = Stack {
+                item: item.clone(),
+                quantity: slot.quantity,
+                durability: slot.durability,
+            };
+            if item.bind().max_durability > 0 {
+                totals.push(stack);
+                continue;
+            }
+            let id = stack.id();
+            match totals.iter_mut().find(|existing| existing.id() == id) {
+                Some(existing) => existing.quantity += stack.quantity,
+                None => totals.push(stack),
+            }
+        }
+
+        let mut stacks = Vec::new();
+        for mut total in totals {
+            let max_stack = total.item.bind().get_max_stack().max(1);
+            while total.quantity > 0 {
+                let to_add = max_stack.min(total.quantity);
+                stacks.push(Stack {
+                    quantity: to_add,
+                    ..total.clone()
+                });
+                total.quantity -= to_add;
+            }
+        }
+        stacks
+    }
+
+    fn write_stacks(&mut self, stacks: Vec<Stack>) {
+        let mut stacks = stacks.into_iter();
+        for mut slot in self.slots.iter_shared().skip(self.hotbar_size as usize) {
+            let mut slot = slot.bind_mut();
+            if slot.is_filtered() {
+                continue;
+            }
+            match stacks.next() {
+                Some(stack) => {
+                    slot.item = Some(stack.item);
+                    slot.quantity = stack.quantity;
+                    slot.durability = stack.durability;
+                }
+                None => {
+                    slot.item = None;
+                    slot.quantity = 0;
+                    slot.durability = 0;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Stack {
+    pub item: Gd<InventoryItem>,
+    pub quantity: u32,
+    pub durability: u32,
+}
+
+impl Stack {
+    fn id(&self) -> String {
+        self.item.bind().id().to_string()
+    }
 }
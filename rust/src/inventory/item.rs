@@ -1,5 +1,82 @@
 use godot::{classes::Texture2D, prelude::*};
 
+use crate::stats::StatModifiers;
+
+#[derive(
+    GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+#[godot(via = i64)]
+pub enum ItemCategory {
+    #[default]
+    Misc,
+    Ore,
+    Tool,
+    Consumable,
+    Ammo,
+    Placeable,
+}
+
+impl ItemCategory {
+    pub fn as_tag(self) -> &'static str {
+        match self {
+            ItemCategory::Misc => "misc",
+            ItemCategory::Ore => "ore",
+            ItemCategory::Tool => "tool",
+            ItemCategory::Consumable => "consumable",
+            ItemCategory::Ammo => "ammo",
+            ItemCategory::Placeable => "placeable",
+        }
+    }
+}
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::WHITE,
+            Rarity::Uncommon => Color::from_rgb(0.3, 0.85, 0.3),
+            Rarity::Rare => Color::from_rgb(0.3, 0.55, 1.0),
+            Rarity::Epic => Color::from_rgb(0.7, 0.35, 0.95),
+            Rarity::Legendary => Color::from_rgb(1.0, 0.6, 0.1),
+        }
+    }
+}
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum UseEffect {
+    #[default]
+    None,
+    Heal,
+    RestoreStamina,
+    // Applies `modifiers` for `effect_duration` seconds
+    Buff,
+    // Reveals `effect_amount` pixels around the player
+    RevealMap,
+    // Removes the status effects named by `cures`
+    Cure,
+}
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum PickupMode {
+    // Follows the player's auto pickup setting
+    #[default]
+    Default,
+    Manual,
+    Auto,
+}
+
 #[derive(GodotClass, Debug)]
 #[class(init, base=Resource)]
 pub struct InventoryItem {
@@ -8,6 +85,21 @@ pub struct InventoryItem {
     #[export]
     name: StringName,
 
+    #[export]
+    pub display_name: GString,
+
+    #[export(multiline)]
+    pub description: GString,
+
+    #[export]
+    pub rarity: Rarity,
+
+    #[export]
+    pub category: ItemCategory,
+
+    #[export]
+    pub tags: Array<StringName>,
+
     #[export]
     pub icon_path: Option<Gd<Texture2D>>,
 
@@ -21,14 +113,71 @@ pub struct InventoryItem {
     #[init(val = 1)]
     pub max_stack: u32,
 
+    // 0 means the item never wears out
+    #[export]
+    pub max_durability: u32,
+
     #[export]
     pub equippable: bool,
 
     #[export]
     pub equip_path: Option<Gd<PackedScene>>,
+
+    #[export]
+    pub modifiers: Option<Gd<StatModifiers>>,
+
+    #[export]
+    pub pickup_mode: PickupMode,
+
+    #[export]
+    pub use_effect: UseEffect,
+
+    #[export]
+    pub effect_amount: f32,
+
+    #[export]
+    pub effect_duration: f32,
+
+    #[export]
+    pub cures: StringName,
 }
 
 impl InventoryItem {
+    pub fn id(&self) -> StringName {
+        self.name.clone()
+    }
+
+    // Whether using it would do anything, so it isn't used up for nothing
+    pub fn is_consumable(&self) -> bool {
+        match self.use_effect {
+            UseEffect::None => false,
+            UseEffect::Buff => self.modifiers.is_some(),
+            _ => true,
+        }
+    }
+
+    // Slots and pickables set up in the editor have 0 durability, they count as new
+    pub fn durability_or_new(&self, durability: u32) -> u32 {
+        if durability == 0 {
+            self.max_durability
+        } else {
+            durability
+        }
+    }
+
+    pub fn title(&self) -> GString {
+        if self.display_name.is_empty() {
+            self.name.to_string().to_godot()
+        } else {
+            self.display_name.clone()
+        }
+    }
+
+    // The category always counts as a tag, so "ore" matches every ore item
+    pub fn has_tag(&self, tag: &StringName) -> bool {
+        tag.to_string() == self.category.as_tag() || self.tags.contains(tag)
+    }
+
     #[allow(dead_code)]
     pub fn icon(&self) -> &Gd<Texture2D> {
         self.icon_path.as_ref().unwrap()
@@ -10,4 +10,25 @@ pub struct InventorySlot {
     pub item: Option<Gd<InventoryItem>>,
     #[export]
     pub quantity: u32,
+    #[export]
+    pub durability: u32,
+    // Empty means the slot accepts any item
+    #[export]
+    pub accepted_tags: Array<StringName>,
+}
+
+impl InventorySlot {
+    pub fn is_filtered(&self) -> bool {
+        !self.accepted_tags.is_empty()
+    }
+
+    pub fn accepts(&self, item: &Gd<InventoryItem>) -> bool {
+        if !self.is_filtered() {
+            return true;
+        }
+        let item = item.bind();
+        self.accepted_tags
+            .iter_shared()
+            .any(|tag| item.has_tag(&tag))
+    }
 }
@@ -1,9 +1,13 @@
 use godot::{
-    classes::{Control, GridContainer, IControl, NinePatchRect, TextureButton},
+    classes::{Button, Control, GridContainer, IControl, NinePatchRect, TextureButton},
     prelude::*,
 };
 
-use crate::inventory::{inv::Inventory, ui::slot::InventorySlotUI};
+use crate::inventory::{
+    inv::{Inventory, SortMode},
+    slot::InventorySlot,
+    ui::slot::InventorySlotUI,
+};
 
 #[derive(GodotClass)]
 #[class(init, base=Control)]
@@ -19,6 +23,12 @@ pub struct InventoryUI {
     #[export]
     hotbar_spawn_point: Option<Gd<GridContainer>>,
 
+    #[export]
+    sort_button: Option<Gd<Button>>,
+
+    #[export]
+    sort_mode: SortMode,
+
     #[var]
     pub inventory: Option<Gd<Inventory>>,
 
@@ -48,8 +58,15 @@ impl IControl for InventoryUI {
             godot_warn!("InventoryUI: inventory node is not set");
         }
         self.inventory_node_mut().set_visible(false);
+        if let Some(sort_button) = self.sort_button.clone() {
+            sort_button
+                .signals()
+                .pressed()
+                .connect_other(self, Self::sort);
+        } else {
+            godot_warn!("InventoryUI: sort button is not set");
+        }
         let slots = self.inventory().bind().get_slots();
-        let hotbar_size = self.inventory().bind().hotbar_size as usize;
         {
             let inventory = self.inventory().bind();
             if inventory.size + inventory.hotbar_size != slots.len() as u32 {
@@ -61,48 +78,14 @@ impl IControl for InventoryUI {
             }
         }
 
-        for (idx, i) in slots.iter_shared().enumerate() {
-            let scene = load::<PackedScene>("res://scenes/ui/inventory_slot.tscn");
-            let Some(scene) = scene.instantiate() else {
-                godot_error!("Failed to instantiate inventory slot scene");
-                return;
-            };
-            let slot = i.bind();
-            {
-                let slot_ui = scene.to_godot().cast::<TextureButton>();
-                let idx = idx as u32;
-                let self_gd = self.to_gd();
-                let self_gd = self_gd.clone();
-                let func = move || {
-                    // Use the owned Gd (not GdRef)
-                    let mut self_gd = self_gd.clone(); // Clone Gd (reference counted)
-                    self_gd.bind_mut().signals().slot_clicked().emit(idx);
-                };
-
-                slot_ui.signals().pressed().connect(func);
-            }
-            let mut slot_ui = scene.cast::<InventorySlotUI>();
-            {
-                let mut slot_ui = slot_ui.bind_mut();
-                slot_ui.item = slot.item.clone();
-                slot_ui.quantity = slot.quantity;
-            }
-            if idx < hotbar_size {
-                let spawn_point = self.hotbar_spawn_point_mut();
-                spawn_point.add_child(&slot_ui);
-                self.slots.push(slot_ui);
-            } else {
-                let spawn_point = self.inv_spawn_point_mut();
-                spawn_point.add_child(&slot_ui);
-                self.slots.push(slot_ui);
-            }
-        }
+        self.sync_slot_count(&slots);
     }
 }
 
 impl InventoryUI {
     pub fn refresh(&mut self) {
         let slots = self.inventory().bind().get_slots();
+        self.sync_slot_count(&slots);
         for (slot, slot_ui) in slots.iter_shared().zip(self.slots.iter_mut()) {
             let mut slot_ui = slot_ui.bind_mut();
             let slot = slot.bind();
@@ -115,18 +98,92 @@ impl InventoryUI {
                 slot_ui.quantity = slot.quantity;
                 diff = true;
             }
+            if slot_ui.durability != slot.durability {
+                slot_ui.durability = slot.durability;
+                diff = true;
+            }
             if diff {
                 slot_ui.refresh();
             }
         }
     }
 
+    // Adds or frees slot widgets so there is exactly one per inventory slot
+    fn sync_slot_count(&mut self, slots: &Array<Gd<InventorySlot>>) {
+        while self.slots.len() > slots.len() {
+            if let Some(mut slot_ui) = self.slots.pop() {
+                slot_ui.queue_free();
+            }
+        }
+        for idx in self.slots.len()..slots.len() {
+            let Some(slot) = slots.get(idx) else {
+                break;
+            };
+            if self.add_slot_ui(idx, &slot).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn add_slot_ui(&mut self, idx: usize, slot: &Gd<InventorySlot>) -> Result<(), ()> {
+        let hotbar_size = self.inventory().bind().hotbar_size as usize;
+        let scene = load::<PackedScene>("res://scenes/ui/inventory_slot.tscn");
+        let Some(scene) = scene.instantiate() else {
+            godot_error!("Failed to instantiate inventory slot scene");
+            return Err(());
+        };
+        let slot = slot.bind();
+        {
+            let slot_ui = scene.to_godot().cast::<TextureButton>();
+            let idx = idx as u32;
+            let self_gd = self.to_gd();
+            let self_gd = self_gd.clone();
+            let func = move || {
+                // Use the owned Gd (not GdRef)
+                let mut self_gd = self_gd.clone(); // Clone Gd (reference counted)
+                self_gd.bind_mut().signals().slot_clicked().emit(idx);
+            };
+
+            slot_ui.signals().pressed().connect(func);
+        }
+        let mut slot_ui = scene.cast::<InventorySlotUI>();
+        {
+            let mut slot_ui = slot_ui.bind_mut();
+            slot_ui.item = slot.item.clone();
+            slot_ui.quantity = slot.quantity;
+            slot_ui.durability = slot.durability;
+        }
+        if idx < hotbar_size {
+            let spawn_point = self.hotbar_spawn_point_mut();
+            spawn_point.add_child(&slot_ui);
+            self.slots.push(slot_ui);
+        } else {
+            let spawn_point = self.inv_spawn_point_mut();
+            spawn_point.add_child(&slot_ui);
+            self.slots.push(slot_ui);
+        }
+        Ok(())
+    }
+
+    pub fn select_slot(&mut self, idx: u32) {
+        for (i, slot_ui) in self.slots.iter_mut().enumerate() {
+            slot_ui.bind_mut().set_active(i as u32 == idx);
+        }
+    }
+
+    pub fn sort(&mut self) {
+        let mode = self.sort_mode;
+        self.inventory_mut().bind_mut().sort(mode);
+        self.refresh();
+    }
+
     pub fn toggle(&mut self) {
         let inv_node = self.inventory_node_mut();
         let is_visible = inv_node.is_visible();
         inv_node.set_visible(!is_visible);
     }
 
+    #[allow(dead_code)]
     fn inventory_node(&self) -> &Gd<NinePatchRect> {
         self.inventory_node
             .as_ref()
@@ -151,6 +208,7 @@ impl InventoryUI {
             .expect("InventoryUI: inventory is not set")
     }
 
+    #[allow(dead_code)]
     fn inv_spawn_point(&self) -> &Gd<GridContainer> {
         self.inv_spawn_point
             .as_ref()
@@ -163,6 +221,7 @@ impl InventoryUI {
             .expect("InventoryUI: inv_spawn_point is not set")
     }
 
+    #[allow(dead_code)]
     fn hotbar_spawn_point(&self) -> &Gd<GridContainer> {
         self.hotbar_spawn_point
             .as_ref()
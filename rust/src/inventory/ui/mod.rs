@@ -1,2 +1,3 @@
 pub mod inv;
 pub mod slot;
+pub mod tooltip;
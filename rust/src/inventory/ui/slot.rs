@@ -1,9 +1,9 @@
 use godot::{
-    classes::{ITextureButton, Label, TextureButton, TextureRect},
+    classes::{ITextureButton, Label, ShaderMaterial, TextureButton, TextureRect},
     prelude::*,
 };
 
-use crate::inventory::item::InventoryItem;
+use crate::inventory::{item::InventoryItem, ui::tooltip::ItemTooltip};
 
 #[derive(GodotClass)]
 #[class(init, base=TextureButton)]
@@ -15,6 +15,7 @@ pub struct InventorySlotUI {
     label: Option<Gd<Label>>,
     pub item: Option<Gd<InventoryItem>>,
     pub quantity: u32,
+    pub durability: u32,
 }
 
 #[godot_api]
@@ -28,6 +29,20 @@ impl ITextureButton for InventorySlotUI {
         }
         self.refresh();
     }
+
+    fn make_custom_tooltip(&self, _for_text: GString) -> Option<Gd<Object>> {
+        let item = self.item.as_ref()?;
+        let scene = load::<PackedScene>("res://scenes/ui/item_tooltip.tscn");
+        let Some(tooltip) = scene.instantiate() else {
+            godot_error!("Failed to instantiate item tooltip scene");
+            return None;
+        };
+        let mut tooltip = tooltip.cast::<ItemTooltip>();
+        tooltip
+            .bind_mut()
+            .show_item(item, self.quantity, self.durability);
+        Some(tooltip.upcast())
+    }
 }
 
 impl InventorySlotUI {
@@ -37,6 +52,8 @@ impl InventorySlotUI {
         match item {
             Some(ref item) => {
                 let item = item.bind();
+                // Godot only asks for the custom tooltip when there is tooltip text
+                self.base_mut().set_tooltip_text(&item.title());
                 let texture = self.texture_mut();
                 texture.set_scale(Vector2::new(item.icon_scale, item.icon_scale));
                 texture.set_pivot_offset(item.icon_offset);
@@ -50,6 +67,7 @@ impl InventorySlotUI {
                 }
             }
             None => {
+                self.base_mut().set_tooltip_text("");
                 let texture = self.texture_mut();
                 texture.set_scale(Vector2::new(1.0, 1.0));
                 texture.set_pivot_offset(Vector2::new(0.0, 0.0));
@@ -60,6 +78,19 @@ impl InventorySlotUI {
         }
     }
 
+    // Drives the `active` uniform of the hover shader to highlight the selected slot
+    pub fn set_active(&mut self, active: bool) {
+        let Some(material) = self.base().get_material() else {
+            return;
+        };
+        if !material.is_class("ShaderMaterial") {
+            return;
+        }
+        let mut material: Gd<ShaderMaterial> = material.cast();
+        material.set_shader_parameter("active", &Variant::from(active));
+    }
+
+    #[allow(dead_code)]
     fn texture(&self) -> &Gd<TextureRect> {
         self.texture
             .as_ref()
@@ -72,6 +103,7 @@ impl InventorySlotUI {
             .expect("InventorySlotUI: texture is not set")
     }
 
+    #[allow(dead_code)]
     fn label(&self) -> &Gd<Label> {
         self.label
             .as_ref()
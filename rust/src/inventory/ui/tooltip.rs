@@ -0,0 +1,72 @@
+use godot::{
+    classes::{IPanelContainer, Label, PanelContainer},
+    prelude::*,
+};
+
+use crate::inventory::item::{InventoryItem, UseEffect};
+
+#[derive(GodotClass)]
+#[class(init, base=PanelContainer)]
+pub struct ItemTooltip {
+    base: Base<PanelContainer>,
+    #[export]
+    title: Option<Gd<Label>>,
+    #[export]
+    details: Option<Gd<Label>>,
+}
+
+#[godot_api]
+impl IPanelContainer for ItemTooltip {
+    fn ready(&mut self) {
+        if self.title.is_none() {
+            godot_warn!("ItemTooltip: title is not set");
+        }
+        if self.details.is_none() {
+            godot_warn!("ItemTooltip: details is not set");
+        }
+    }
+}
+
+impl ItemTooltip {
+    pub fn show_item(&mut self, item: &Gd<InventoryItem>, quantity: u32, durability: u32) {
+        let item = item.bind();
+        let title = self.title_mut();
+        title.set_text(&item.title());
+        title.add_theme_color_override("font_color", item.rarity.color());
+
+        let mut lines = vec![
+            format!("{:?} - {:?}", item.rarity, item.category),
+            format!("{}/{}", quantity, item.max_stack),
+        ];
+        if item.max_durability > 0 {
+            lines.push(format!(
+                "Durability: {}/{}",
+                durability, item.max_durability
+            ));
+        }
+        match item.use_effect {
+            UseEffect::None => {}
+            UseEffect::Buff => lines.push(format!("Use: buff for {}s", item.effect_duration)),
+            UseEffect::Cure => lines.push(format!("Use: cures {}", item.cures)),
+            effect => lines.push(format!("Use: {:?} {}", effect, item.effect_amount)),
+        }
+        if let Some(modifiers) = item.modifiers.as_ref() {
+            lines.extend(modifiers.bind().describe());
+        }
+        if !item.description.is_empty() {
+            lines.push(String::new());
+            lines.push(item.description.to_string());
+        }
+        self.details_mut().set_text(lines.join("\n").as_str());
+    }
+
+    fn title_mut(&mut self) -> &mut Gd<Label> {
+        self.title.as_mut().expect("ItemTooltip: title is not set")
+    }
+
+    fn details_mut(&mut self) -> &mut Gd<Label> {
+        self.details
+            .as_mut()
+            .expect("ItemTooltip: details is not set")
+    }
+}
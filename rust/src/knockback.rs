@@ -0,0 +1,64 @@
+use godot::{
+    classes::{CharacterBody2D, RigidBody2D},
+    prelude::*,
+};
+
+use crate::{enemy::body::Enemy, player::Player};
+
+// A shove from a hit or an explosion
+#[derive(Clone, Copy, Debug)]
+pub struct Knockback {
+    pub direction: Vector2,
+    // Pixels per second the target is sent flying at
+    pub strength: f32,
+    // Seconds the target can't steer itself
+    pub stun: f32,
+}
+
+impl Knockback {
+    pub fn velocity(&self) -> Vector2 {
+        self.direction.normalized_or_zero() * self.strength
+    }
+}
+
+// Knocks back whatever `target` is, used by every hit and meant for
+// explosions too. The target must not be bound already, so the player can't
+// knock itself back through this.
+pub fn apply(target: Gd<Node2D>, knockback: Knockback) {
+    let target = match target.try_cast::<Player>() {
+        Ok(mut player) => return player.bind_mut().knockback(knockback),
+        Err(target) => target,
+    };
+    let target = match target.try_cast::<Enemy>() {
+        Ok(mut enemy) => return enemy.bind_mut().knockback(knockback),
+        Err(target) => target,
+    };
+    if let Ok(mut body) = target.try_cast::<RigidBody2D>() {
+        let impulse = knockback.velocity() * body.get_mass();
+        body.apply_central_impulse_ex().impulse(impulse).done();
+    }
+}
+
+// Shoves the rigid bodies a character slid into sideways, crates and the like
+pub fn push_rigid_bodies(body: &mut CharacterBody2D, force: f32) {
+    for i in 0..body.get_slide_collision_count() {
+        let Some(collision) = body.get_slide_collision(i) else {
+            continue;
+        };
+        let Some(collider) = collision.get_collider() else {
+            continue;
+        };
+        let Ok(mut pushed) = collider.try_cast::<RigidBody2D>() else {
+            continue;
+        };
+        let normal = -collision.get_normal();
+        // Standing on top of it isn't pushing it
+        if normal.y.abs() > 0.7 {
+            continue;
+        }
+        pushed
+            .apply_central_impulse_ex()
+            .impulse(normal * force)
+            .done();
+    }
+}
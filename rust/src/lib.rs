@@ -1,10 +1,28 @@
 use godot::prelude::*;
 
+mod day_cycle;
 mod drill;
+mod enemy;
+mod health;
+mod health_ui;
 mod inventory;
+mod knockback;
+mod loot;
 mod map;
+mod movement;
+mod pathfinding;
 mod pickable;
+mod pickable_manager;
 mod player;
+mod projectile;
+mod projectile_manager;
+mod resource_node;
+mod spawner;
+mod stats;
+mod status;
+mod tile_health;
+mod tool;
+mod weapon;
 
 #[derive(GodotClass)]
 #[class(base=Node)]
@@ -0,0 +1,239 @@
+use godot::prelude::*;
+
+use crate::inventory::item::InventoryItem;
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct LootEntry {
+    base: Base<Resource>,
+
+    #[export]
+    pub item: Option<Gd<InventoryItem>>,
+
+    #[export]
+    #[init(val = 1)]
+    pub min_quantity: u32,
+
+    #[export]
+    #[init(val = 1)]
+    pub max_quantity: u32,
+
+    // Relative to the other entries of the table
+    #[export]
+    #[init(val = 1.0)]
+    pub weight: f32,
+
+    // Chance this entry actually drops once it's been picked, 1 always drops
+    #[export]
+    #[init(val = 1.0)]
+    pub chance: f32,
+
+    #[export]
+    pub min_tool_tier: u32,
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct LootTable {
+    base: Base<Resource>,
+
+    // How many entries are picked per roll
+    #[export]
+    #[init(val = 1)]
+    pub rolls: u32,
+
+    #[export]
+    pub entries: Array<Gd<LootEntry>>,
+}
+
+impl LootEntry {
+    fn odds(&self) -> Odds {
+        Odds {
+            weight: self.weight,
+            chance: self.chance,
+            min_quantity: self.min_quantity,
+            max_quantity: self.max_quantity,
+            min_tool_tier: self.min_tool_tier,
+        }
+    }
+}
+
+impl LootTable {
+    // `rng` yields values in [0, 1), seeded the same way it always gives the same drops
+    pub fn roll(&self, tool_tier: u32, rng: impl FnMut() -> f32) -> Vec<(Gd<InventoryItem>, u32)> {
+        let entries: Vec<(Odds, Option<Gd<InventoryItem>>)> = self
+            .entries
+            .iter_shared()
+            .map(|entry| {
+                let entry = entry.bind();
+                (entry.odds(), entry.item.clone())
+            })
+            .collect();
+        roll(&entries, self.rolls, tool_tier, rng)
+            .into_iter()
+            .filter_map(|(item, quantity)| Some((item.clone()?, quantity)))
+            .collect()
+    }
+}
+
+// What a LootEntry drops without the item, so rolls work outside the engine
+#[derive(Clone, Copy, Debug)]
+struct Odds {
+    weight: f32,
+    chance: f32,
+    min_quantity: u32,
+    max_quantity: u32,
+    min_tool_tier: u32,
+}
+
+fn roll<T>(
+    entries: &[(Odds, T)],
+    rolls: u32,
+    tool_tier: u32,
+    mut rng: impl FnMut() -> f32,
+) -> Vec<(&T, u32)> {
+    let entries: Vec<&(Odds, T)> = entries
+        .iter()
+        .filter(|(odds, _)| odds.min_tool_tier <= tool_tier)
+        .collect();
+    let total: f32 = entries.iter().map(|(odds, _)| odds.weight.max(0.0)).sum();
+    let mut drops = Vec::new();
+    if total <= 0.0 {
+        return drops;
+    }
+
+    for _ in 0..rolls {
+        let mut pick = rng() * total;
+        let Some((odds, item)) = entries
+            .iter()
+            .find(|(odds, _)| {
+                let weight = odds.weight.max(0.0);
+                if pick < weight {
+                    return true;
+                }
+                pick -= weight;
+                false
+            })
+            .or(entries.last())
+        else {
+            continue;
+        };
+        if rng() > odds.chance {
+            continue;
+        }
+        let span = odds.max_quantity.saturating_sub(odds.min_quantity) + 1;
+        let quantity = odds.min_quantity + ((rng() * span as f32) as u32).min(span - 1);
+        if quantity > 0 {
+            drops.push((item, quantity));
+        }
+    }
+    drops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn odds(weight: f32) -> Odds {
+        Odds {
+            weight,
+            chance: 1.0,
+            min_quantity: 1,
+            max_quantity: 1,
+            min_tool_tier: 0,
+        }
+    }
+
+    // xorshift, enough to stand in for the engine's RNG
+    fn seeded(seed: u64) -> impl FnMut() -> f32 {
+        let mut state = seed;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 40) as f32 / (1u64 << 24) as f32
+        }
+    }
+
+    #[test]
+    fn picks_entries_by_weight() {
+        let entries = [
+            (odds(3.0), "stone"),
+            (odds(0.0), "gem"),
+            (odds(1.0), "coal"),
+        ];
+        // Picks land in [0, 3) for stone and [3, 4) for coal, gem is never hit
+        let picks = [0.0, 0.74, 0.75, 0.99];
+        let dropped: Vec<&str> = picks
+            .iter()
+            .flat_map(|&pick| {
+                let mut values = [pick, 0.0, 0.0].into_iter();
+                roll(&entries, 1, 0, move || values.next().unwrap())
+            })
+            .map(|(item, _)| *item)
+            .collect();
+        assert_eq!(dropped, ["stone", "stone", "coal", "coal"]);
+
+        let mut rng = seeded(7);
+        let stones = roll(&entries, 4000, 0, &mut rng)
+            .iter()
+            .filter(|(item, _)| **item == "stone")
+            .count();
+        assert!((2800..3200).contains(&stones), "{stones}");
+    }
+
+    #[test]
+    fn quantities_stay_in_range() {
+        let entries = [(
+            Odds {
+                min_quantity: 2,
+                max_quantity: 5,
+                ..odds(1.0)
+            },
+            "ore",
+        )];
+        let drops = roll(&entries, 1000, 0, seeded(11));
+        assert_eq!(drops.len(), 1000);
+        for quantity in 2..=5 {
+            assert!(drops.iter().any(|&(_, q)| q == quantity), "{quantity}");
+        }
+        assert!(drops.iter().all(|&(_, q)| (2..=5).contains(&q)));
+    }
+
+    #[test]
+    fn gates_entries_by_tool_tier() {
+        let entries = [
+            (odds(1.0), "stone"),
+            (
+                Odds {
+                    min_tool_tier: 2,
+                    ..odds(1.0)
+                },
+                "diamond",
+            ),
+        ];
+        let low = roll(&entries, 200, 1, seeded(3));
+        assert!(low.iter().all(|(item, _)| **item == "stone"));
+        let high = roll(&entries, 200, 2, seeded(3));
+        assert!(high.iter().any(|(item, _)| **item == "diamond"));
+    }
+
+    #[test]
+    fn same_seed_same_drops() {
+        let entries = [
+            (
+                Odds {
+                    chance: 0.5,
+                    max_quantity: 3,
+                    ..odds(2.0)
+                },
+                "wood",
+            ),
+            (odds(1.0), "sap"),
+        ];
+        let first = roll(&entries, 50, 0, seeded(42));
+        let second = roll(&entries, 50, 0, seeded(42));
+        assert_eq!(first, second);
+        assert_ne!(first, roll(&entries, 50, 0, seeded(43)));
+    }
+}
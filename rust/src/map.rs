@@ -1,32 +1,39 @@
+use std::io::{Read, Write};
+
 use bincode::{
     Decode, Encode,
     config::{self},
 };
-use godot::{classes::file_access::ModeFlags, prelude::*};
+use godot::{
+    classes::{file_access::ModeFlags, notify::NodeNotification},
+    prelude::*,
+};
+
+use crate::{
+    day_cycle::DayCycle, enemy::body::Enemy, resource_node::ResourceNode,
+    spawner::manager::Spawner, tile_health::TileHealth,
+};
+
+// 2 added the save payload after the header
+const SAVE_VERSION: u32 = 2;
 
 #[derive(GodotClass)]
-#[class(base=Node)]
+#[class(init, base=Node)]
 pub struct MapManager {
     base: Base<Node>,
 
-    resources: Vec<Resources>,
-    tiles: Vec<Tiles>,
-    entities: Vec<Entities>,
-    objects: Vec<Objects>,
     #[var]
     filename: GString,
-}
 
-pub struct FileHeader {
-    pub name: GString,
-    pub version: u32,
-    pub resources_file: GString,
-    pub tiles_file: GString,
-    pub entities_file: GString,
-    pub objects_file: GString,
+    // Seconds between autosaves, 0 only saves when the game is closed
+    #[export]
+    #[init(val = 60.0)]
+    autosave_interval: f32,
+
+    autosave_timer: f32,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Clone)]
 pub struct Vector2Mem {
     pub x: f32,
     pub y: f32,
@@ -42,11 +49,14 @@ pub struct ResourcesMem {
 #[derive(Encode, Decode)]
 pub struct TilesMem {
     pub map_pos: Vector2Mem,
+    // -1 for a tile that was broken
+    pub source: i32,
     pub atlas_pos: Vector2Mem,
+    // 0 for a tile that was broken
     pub health: u8,
 }
 
-#[derive(Encode, Decode)]
+#[derive(Encode, Decode, Clone)]
 pub struct EntitiesMem {
     pub pos: Vector2Mem,
     pub scene: String,
@@ -60,56 +70,149 @@ pub struct ObjectsMem {
     pub scene: String,
 }
 
+#[derive(Encode, Decode)]
+pub struct DayMem {
+    pub day: u32,
+    pub time: f32,
+}
+
+#[derive(Encode, Decode)]
+pub struct HordeMem {
+    pub night: u32,
+    pub wave: u32,
+    pub spawned: u32,
+    pub timer: f32,
+}
+
+#[derive(Encode, Decode)]
+pub struct EventsMem {
+    // Last night that was rolled for a horde, so reloading can't re-roll it
+    pub rolled_night: Option<u32>,
+    pub horde: Option<HordeMem>,
+}
+
+// Everything in a save besides the header
+#[derive(Encode, Decode, Default)]
+pub struct SaveMem {
+    pub resources: Vec<ResourcesMem>,
+    pub tiles: Vec<TilesMem>,
+    pub entities: Vec<EntitiesMem>,
+    pub objects: Vec<ObjectsMem>,
+    pub day: Option<DayMem>,
+    pub events: Option<EventsMem>,
+}
+
 #[godot_api]
 impl INode for MapManager {
     fn ready(&mut self) {
-        self.save_header();
-        let res = self.is_valid();
-        match res {
-            Ok(_) => godot_print!("Map file is valid"),
-            Err(_) => godot_print!("Map file is invalid"),
+        if self.filename.is_empty() {
+            godot_warn!("MapManager: filename is not set");
+            return;
+        }
+        match self.is_valid() {
+            // Once the rest of the scene is ready to take the saved state
+            Ok(_) => {
+                self.base_mut().call_deferred("load_map", &[]);
+            }
+            Err(err) => godot_print!("No map loaded: {}", err),
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        if self.autosave_interval <= 0.0 {
+            return;
+        }
+        self.autosave_timer += delta as f32;
+        if self.autosave_timer >= self.autosave_interval {
+            self.autosave_timer = 0.0;
+            self.save_map();
+        }
+    }
+
+    fn on_notification(&mut self, what: NodeNotification) {
+        if what == NodeNotification::WM_CLOSE_REQUEST {
+            self.save_map();
         }
     }
 }
 
+#[godot_api]
 impl MapManager {
-    pub fn serialize_chunk<T: Encode>(&self, data: &T) -> Vec<u8> {
-        let config = config::standard();
-        bincode::encode_to_vec(data, config).expect("Failed to serialize chunk")
+    #[func]
+    pub fn save_map(&mut self) {
+        if let Err(err) = self.save() {
+            godot_warn!("Failed to save map: {}", err);
+        }
+    }
+
+    #[func]
+    pub fn load_map(&mut self) {
+        if let Err(err) = self.load() {
+            godot_warn!("Failed to load map: {}", err);
+        }
     }
+}
 
-    pub fn save_header(&self) {
+impl MapManager {
+    fn key() -> PackedByteArray {
         let mut key = PackedByteArray::new();
         for _ in 0..32 {
             key.push(u8::MAX);
         }
-        let mut file = GFile::open_encrypted(
-            &format!("res://saves/{}", self.filename).to_godot(),
-            ModeFlags::WRITE,
-            &key,
-        )
-        .expect("Failed to open map file");
+        key
+    }
+
+    fn path(&self) -> GString {
+        format!("res://saves/{}", self.filename).to_godot()
+    }
+
+    pub fn serialize_chunk<T: Encode>(&self, data: &T) -> Vec<u8> {
+        let config = config::standard();
+        bincode::encode_to_vec(data, config).expect("Failed to serialize chunk")
+    }
+
+    pub fn save(&self) -> Result<(), GString> {
+        let data = self.serialize_chunk(&self.collect());
+        let Ok(mut file) = GFile::open_encrypted(&self.path(), ModeFlags::WRITE, &Self::key())
+        else {
+            return Err("Failed to open map file".to_godot());
+        };
         file.write_pascal_string(&self.filename)
-            .expect("Failed to write map name");
-        //let mut offset = file.position();
-        file.write_u32(1).expect("Failed to write map version");
+            .map_err(|_| "Failed to write map name".to_godot())?;
+        file.write_u32(SAVE_VERSION)
+            .map_err(|_| "Failed to write map version".to_godot())?;
+        file.write_u32(data.len() as u32)
+            .map_err(|_| "Failed to write map size".to_godot())?;
+        file.write_all(&data)
+            .map_err(|_| "Failed to write map data".to_godot())?;
+        Ok(())
     }
 
-    pub fn store<T: Encode>(&self, data: &T) -> Result<(), GString> {
-        let data = self.serialize_chunk(data);
+    pub fn load(&mut self) -> Result<(), GString> {
+        let mut file = self.open_valid()?;
+        let Ok(len) = file.read_u32() else {
+            return Err("Failed to read map size".to_godot());
+        };
+        let mut data = vec![0; len as usize];
+        if file.read_exact(&mut data).is_err() {
+            return Err("Failed to read map data".to_godot());
+        }
+        let Ok((mem, _)) = bincode::decode_from_slice::<SaveMem, _>(&data, config::standard())
+        else {
+            return Err("Failed to decode map data".to_godot());
+        };
+        self.restore(mem);
         Ok(())
     }
 
     pub fn is_valid(&self) -> Result<(), GString> {
-        let mut key = PackedByteArray::new();
-        for _ in 0..32 {
-            key.push(u8::MAX);
-        }
-        let Ok(mut file) = GFile::open_encrypted(
-            &format!("res://saves/{}", self.filename).to_godot(),
-            ModeFlags::READ,
-            &key,
-        ) else {
+        self.open_valid().map(|_| ())
+    }
+
+    // Opens the save and reads past its header
+    fn open_valid(&self) -> Result<GFile, GString> {
+        let Ok(mut file) = GFile::open_encrypted(&self.path(), ModeFlags::READ, &Self::key())
+        else {
             return Err("Failed to open map file".to_godot());
         };
         let Ok(save_name) = file.read_pascal_string() else {
@@ -121,38 +224,95 @@ impl MapManager {
         godot_print!("Save name: {}", save_name);
         godot_print!("Save version: {}", save_version);
         match save_version {
-            1 => Ok(()),
+            SAVE_VERSION => Ok(file),
             _ => Err("Invalid save version".to_godot()),
         }
     }
 
-    pub fn get_by_player_pos(
-        &self,
-        pos: Vector2,
-    ) -> Result<(Vec<Resources>, Vec<Tiles>, Vec<Entities>, Vec<Objects>), GString> {
-        let mut key = PackedByteArray::new();
-        for _ in 0..32 {
-            key.push(u8::MAX);
+    fn nodes_in_group(&self, group: &str) -> Vec<Gd<Node>> {
+        let Some(mut tree) = self.base().get_tree() else {
+            return Vec::new();
+        };
+        tree.get_nodes_in_group(group).iter_shared().collect()
+    }
+
+    fn collect(&self) -> SaveMem {
+        let resources = self
+            .nodes_in_group("resource_node")
+            .into_iter()
+            .filter_map(|node| node.try_cast::<ResourceNode>().ok())
+            .filter(|node| !node.is_queued_for_deletion())
+            .map(|node| node.bind().to_mem())
+            // Nodes that don't come from a scene file can't be restored
+            .filter(|mem| !mem.scene.is_empty())
+            .collect();
+        let mut entities: Vec<EntitiesMem> = self
+            .nodes_in_group("enemy")
+            .into_iter()
+            .filter_map(|node| node.try_cast::<Enemy>().ok())
+            .filter(|enemy| !enemy.is_queued_for_deletion())
+            .map(|enemy| enemy.bind().to_mem())
+            .filter(|mem| !mem.scene.is_empty())
+            .collect();
+        let spawner = Spawner::find(&self.to_gd().upcast());
+        if let Some(spawner) = &spawner {
+            entities.extend(spawner.bind().stored_mem());
         }
-        let mut file = GFile::open_encrypted(
-            &format!("res://saves/{}", self.filename).to_godot(),
-            ModeFlags::READ,
-            &key,
-        )
-        .ok()?;
-        let save_name = file.read_pascal_string().ok()?;
-        let save_version = file.read_u32().ok()?;
-        godot_print!("Save name: {}", save_name);
-        godot_print!("Save version: {}", save_version);
-        match save_version {
-            1 => {
-                let resources = file.read_u32().ok()? as u8;
-                let tiles = file.read_u32().ok()? as u8;
-                let entities = file.read_u32().ok()? as u8;
-                let objects = file.read_u32().ok()? as u8;
-                Ok((resources, tiles, entities, objects))
+        let tiles = TileHealth::find(&self.to_gd().upcast())
+            .map(|tile_health| tile_health.bind().to_mem())
+            .unwrap_or_default();
+        SaveMem {
+            resources,
+            tiles,
+            entities,
+            day: DayCycle::find(&self.to_gd().upcast()).map(|day_cycle| day_cycle.bind().to_mem()),
+            events: spawner.map(|spawner| spawner.bind().to_mem()),
+            ..Default::default()
+        }
+    }
+
+    // Saved nodes replace the ones the level scene starts with
+    fn restore(&mut self, mem: SaveMem) {
+        let Some(mut parent) = self.base().get_parent() else {
+            return;
+        };
+        for mut node in self.nodes_in_group("resource_node") {
+            node.queue_free();
+        }
+        for resource in mem.resources.iter() {
+            if let Some(node) = ResourceNode::from_mem(resource) {
+                parent.add_child(&node);
+            }
+        }
+
+        if let Some(mut tile_health) = TileHealth::find(&self.to_gd().upcast()) {
+            tile_health.bind_mut().load_mem(&mem.tiles);
+        }
+
+        if let (Some(day), Some(mut day_cycle)) = (&mem.day, DayCycle::find(&self.to_gd().upcast()))
+        {
+            day_cycle.bind_mut().load_mem(day);
+        }
+
+        for mut enemy in self.nodes_in_group("enemy") {
+            enemy.queue_free();
+        }
+        match Spawner::find(&self.to_gd().upcast()) {
+            // The spawner brings them back as their chunks load
+            Some(mut spawner) => {
+                let mut spawner = spawner.bind_mut();
+                if let Some(events) = &mem.events {
+                    spawner.load_mem(events);
+                }
+                spawner.store_mem(mem.entities);
+            }
+            None => {
+                for entity in mem.entities.iter() {
+                    if let Some(enemy) = Enemy::from_mem(entity) {
+                        parent.add_child(&enemy);
+                    }
+                }
             }
-            _ => None,
         }
     }
 }
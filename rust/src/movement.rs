@@ -0,0 +1,326 @@
+use godot::prelude::*;
+
+// Seconds the landing animation holds the player before Idle/Move take over
+const LAND_TIME: f32 = 0.1;
+// Seconds a wall jump ignores the wall it just left
+const WALL_JUMP_TIME: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum State {
+    Idle,
+    Move,
+    Jump,
+    Fall,
+    Land,
+    WallSlide,
+    WallJump,
+    Climb,
+}
+
+// Everything the transitions look at, sampled once per frame
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Inputs {
+    pub on_floor: bool,
+    pub on_wall: bool,
+    pub on_ladder: bool,
+    pub direction: f32,
+    // Negative is up, like `ui_up`/`ui_down`
+    pub climb: f32,
+    // Pressed recently enough for the jump buffer
+    pub jump: bool,
+    // Left the floor recently enough to still jump
+    pub coyote: bool,
+    pub velocity: Vector2,
+}
+
+impl State {
+    // Pure so the transitions can be checked without a scene
+    pub fn next(self, inputs: &Inputs, time_in_state: f32) -> State {
+        if inputs.on_ladder && inputs.climb != 0.0 && self != State::Climb {
+            return State::Climb;
+        }
+        match self {
+            State::Idle | State::Move => {
+                if inputs.jump && inputs.on_floor {
+                    State::Jump
+                } else if !inputs.on_floor {
+                    State::Fall
+                } else {
+                    Self::grounded(inputs)
+                }
+            }
+            State::Jump => {
+                if inputs.velocity.y < 0.0 {
+                    State::Jump
+                } else {
+                    Self::airborne(inputs)
+                }
+            }
+            State::Fall => {
+                if inputs.jump && inputs.coyote {
+                    State::Jump
+                } else {
+                    Self::airborne(inputs)
+                }
+            }
+            State::Land => {
+                if inputs.jump {
+                    State::Jump
+                } else if !inputs.on_floor {
+                    State::Fall
+                } else if time_in_state >= LAND_TIME {
+                    Self::grounded(inputs)
+                } else {
+                    State::Land
+                }
+            }
+            State::WallSlide => {
+                if inputs.on_floor {
+                    State::Land
+                } else if inputs.jump {
+                    State::WallJump
+                } else if !inputs.on_wall {
+                    State::Fall
+                } else {
+                    State::WallSlide
+                }
+            }
+            State::WallJump => {
+                if inputs.on_floor {
+                    State::Land
+                } else if time_in_state < WALL_JUMP_TIME {
+                    State::WallJump
+                } else if inputs.velocity.y < 0.0 {
+                    State::Jump
+                } else {
+                    State::Fall
+                }
+            }
+            State::Climb => {
+                if inputs.jump {
+                    State::Jump
+                } else if !inputs.on_ladder {
+                    if inputs.on_floor {
+                        Self::grounded(inputs)
+                    } else {
+                        State::Fall
+                    }
+                } else {
+                    State::Climb
+                }
+            }
+        }
+    }
+
+    fn grounded(inputs: &Inputs) -> State {
+        if inputs.direction != 0.0 {
+            State::Move
+        } else {
+            State::Idle
+        }
+    }
+
+    fn airborne(inputs: &Inputs) -> State {
+        // A buffered jump fires on the landing frame
+        if inputs.on_floor && inputs.jump {
+            State::Jump
+        } else if inputs.on_floor {
+            State::Land
+        } else if inputs.on_wall && inputs.velocity.y > 0.0 {
+            State::WallSlide
+        } else {
+            State::Fall
+        }
+    }
+
+    pub fn animation(self) -> &'static str {
+        match self {
+            State::Idle => "idel",
+            State::Move => "move",
+            State::Jump => "jump",
+            State::Fall => "fall",
+            State::Land => "land",
+            State::WallSlide => "wall_slide",
+            State::WallJump => "wall_jump",
+            State::Climb => "climb",
+        }
+    }
+
+    // Used while the sprite sheet has no dedicated animation for the state
+    pub fn fallback_animation(self) -> &'static str {
+        match self {
+            State::Idle | State::Land => "idel",
+            State::Move | State::Climb => "move",
+            State::Jump | State::Fall | State::WallSlide | State::WallJump => "jump",
+        }
+    }
+}
+
+// Moves `current` toward `target` by `rate` units per second, so the result
+// only depends on elapsed time and not on how it's split into frames
+pub fn approach(current: f32, target: f32, rate: f32, delta: f32) -> f32 {
+    let step = rate * delta;
+    if (target - current).abs() <= step {
+        target
+    } else {
+        current + step * (target - current).signum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_floor() -> Inputs {
+        Inputs {
+            on_floor: true,
+            ..Default::default()
+        }
+    }
+
+    fn airborne(velocity_y: f32) -> Inputs {
+        Inputs {
+            velocity: Vector2::new(0.0, velocity_y),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn idle_and_move_follow_the_direction() {
+        let moving = Inputs {
+            direction: 1.0,
+            ..on_floor()
+        };
+        assert_eq!(State::Idle.next(&moving, 0.0), State::Move);
+        assert_eq!(State::Move.next(&on_floor(), 0.0), State::Idle);
+    }
+
+    #[test]
+    fn jumps_only_from_the_floor_or_within_coyote_time() {
+        let jump = Inputs {
+            jump: true,
+            ..on_floor()
+        };
+        assert_eq!(State::Idle.next(&jump, 0.0), State::Jump);
+
+        let late = Inputs {
+            jump: true,
+            ..airborne(10.0)
+        };
+        assert_eq!(State::Fall.next(&late, 0.0), State::Fall);
+        let coyote = Inputs {
+            coyote: true,
+            ..late
+        };
+        assert_eq!(State::Fall.next(&coyote, 0.0), State::Jump);
+    }
+
+    #[test]
+    fn walking_off_a_ledge_falls() {
+        assert_eq!(State::Move.next(&airborne(0.0), 0.0), State::Fall);
+    }
+
+    #[test]
+    fn jump_turns_into_fall_at_the_apex() {
+        assert_eq!(State::Jump.next(&airborne(-100.0), 0.0), State::Jump);
+        assert_eq!(State::Jump.next(&airborne(0.0), 0.0), State::Fall);
+    }
+
+    #[test]
+    fn landing_holds_for_land_time() {
+        assert_eq!(State::Fall.next(&on_floor(), 0.0), State::Land);
+        assert_eq!(State::Land.next(&on_floor(), 0.0), State::Land);
+        assert_eq!(State::Land.next(&on_floor(), LAND_TIME), State::Idle);
+    }
+
+    #[test]
+    fn buffered_jump_fires_on_landing() {
+        let jump = Inputs {
+            jump: true,
+            ..on_floor()
+        };
+        assert_eq!(State::Fall.next(&jump, 0.0), State::Jump);
+    }
+
+    #[test]
+    fn falling_against_a_wall_slides_and_can_wall_jump() {
+        let sliding = Inputs {
+            on_wall: true,
+            ..airborne(50.0)
+        };
+        assert_eq!(State::Fall.next(&sliding, 0.0), State::WallSlide);
+        let jump = Inputs {
+            jump: true,
+            ..sliding
+        };
+        assert_eq!(State::WallSlide.next(&jump, 0.0), State::WallJump);
+        let off_wall = airborne(50.0);
+        assert_eq!(State::WallSlide.next(&off_wall, 0.0), State::Fall);
+    }
+
+    #[test]
+    fn wall_jump_ignores_the_wall_then_keeps_rising_or_falls() {
+        let rising = Inputs {
+            on_wall: true,
+            ..airborne(-100.0)
+        };
+        assert_eq!(State::WallJump.next(&rising, 0.0), State::WallJump);
+        assert_eq!(State::WallJump.next(&rising, WALL_JUMP_TIME), State::Jump);
+        assert_eq!(
+            State::WallJump.next(&airborne(10.0), WALL_JUMP_TIME),
+            State::Fall
+        );
+        assert_eq!(State::WallJump.next(&on_floor(), 0.0), State::Land);
+    }
+
+    #[test]
+    fn ladders_are_climbed_and_jumped_off() {
+        let climbing = Inputs {
+            on_ladder: true,
+            climb: -1.0,
+            ..airborne(0.0)
+        };
+        assert_eq!(State::Fall.next(&climbing, 0.0), State::Climb);
+        assert_eq!(State::Climb.next(&climbing, 0.0), State::Climb);
+        let jump = Inputs {
+            jump: true,
+            ..climbing
+        };
+        assert_eq!(State::Climb.next(&jump, 0.0), State::Jump);
+        assert_eq!(State::Climb.next(&airborne(0.0), 0.0), State::Fall);
+    }
+
+    #[test]
+    fn approach_moves_by_rate_per_second() {
+        assert_eq!(approach(0.0, 300.0, 1000.0, 0.1), 100.0);
+        assert_eq!(approach(0.0, -300.0, 1000.0, 0.1), -100.0);
+    }
+
+    #[test]
+    fn approach_never_overshoots() {
+        assert_eq!(approach(250.0, 300.0, 1000.0, 0.1), 300.0);
+        assert_eq!(approach(300.0, 300.0, 1000.0, 0.1), 300.0);
+        // Deceleration down to a stop
+        assert_eq!(approach(50.0, 0.0, 2000.0, 0.1), 0.0);
+    }
+
+    #[test]
+    fn approach_is_independent_of_the_frame_rate() {
+        let one_step = approach(0.0, 300.0, 1000.0, 0.2);
+        let mut many_steps = 0.0;
+        for _ in 0..20 {
+            many_steps = approach(many_steps, 300.0, 1000.0, 0.01);
+        }
+        assert!((one_step - many_steps).abs() < 1e-3);
+
+        let mut at_60 = 0.0;
+        for _ in 0..60 {
+            at_60 = approach(at_60, 300.0, 200.0, 1.0 / 60.0);
+        }
+        let mut at_144 = 0.0;
+        for _ in 0..144 {
+            at_144 = approach(at_144, 300.0, 200.0, 1.0 / 144.0);
+        }
+        assert!((at_60 - at_144).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+// Tile coordinates, y grows downward like the TileMapLayer
+pub type Cell = (i32, i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Walk,
+    Jump,
+    Fall,
+    Climb,
+}
+
+// What a movement profile can reach, in tiles
+#[derive(Clone, Copy, Debug)]
+pub struct Reach {
+    pub jump_height: i32,
+    // Widest gap that can be jumped over
+    pub jump_distance: i32,
+    pub max_fall: i32,
+    pub can_climb: bool,
+}
+
+// Edges are worked out during the search instead of being stored, so
+// placing or destroying a tile only touches the two sets below
+#[derive(Default)]
+pub struct TileGrid {
+    solid: HashSet<Cell>,
+    climbable: HashSet<Cell>,
+}
+
+impl TileGrid {
+    pub fn clear(&mut self) {
+        self.solid.clear();
+        self.climbable.clear();
+    }
+
+    pub fn set_solid(&mut self, cell: Cell, solid: bool) {
+        if solid {
+            self.solid.insert(cell);
+        } else {
+            self.solid.remove(&cell);
+        }
+    }
+
+    pub fn set_climbable(&mut self, cell: Cell, climbable: bool) {
+        if climbable {
+            self.climbable.insert(cell);
+        } else {
+            self.climbable.remove(&cell);
+        }
+    }
+
+    pub fn is_solid(&self, cell: Cell) -> bool {
+        self.solid.contains(&cell)
+    }
+
+    pub fn is_climbable(&self, cell: Cell) -> bool {
+        self.climbable.contains(&cell)
+    }
+
+    pub fn can_stand(&self, (x, y): Cell) -> bool {
+        !self.is_solid((x, y)) && (self.is_solid((x, y + 1)) || self.is_climbable((x, y)))
+    }
+
+    // First cell at or below `cell` that can be stood in, looking at most `depth`
+    // tiles down and never through solid ones
+    pub fn ground_below(&self, (x, y): Cell, depth: i32) -> Option<Cell> {
+        (y..=y + depth.max(0))
+            .take_while(|&y| !self.is_solid((x, y)))
+            .find(|&y| self.can_stand((x, y)))
+            .map(|y| (x, y))
+    }
+
+    // Every cell from `from` to `to` in the column is open
+    fn column_clear(&self, x: i32, from: i32, to: i32) -> bool {
+        let (top, bottom) = (from.min(to), from.max(to));
+        (top..=bottom).all(|y| !self.is_solid((x, y)))
+    }
+
+    // Moves out of `cell` with their cost, cheaper is better
+    fn neighbours(&self, (x, y): Cell, reach: &Reach) -> Vec<(Cell, Action, u32)> {
+        let mut moves = Vec::new();
+
+        if reach.can_climb && self.is_climbable((x, y)) {
+            for dy in [-1, 1] {
+                let next = (x, y + dy);
+                if !self.is_solid(next) && (self.is_climbable(next) || self.can_stand(next)) {
+                    moves.push((next, Action::Climb, 12));
+                }
+            }
+        }
+
+        for dx in [-1, 1] {
+            let side = (x + dx, y);
+            if self.can_stand(side) {
+                moves.push((side, Action::Walk, 10));
+            } else if !self.is_solid(side) {
+                // Walk off the ledge and drop to the first floor below
+                for drop in 1..=reach.max_fall {
+                    let below = (x + dx, y + drop);
+                    if self.is_solid(below) {
+                        break;
+                    }
+                    if self.can_stand(below) {
+                        moves.push((below, Action::Fall, 10 + 5 * drop as u32));
+                        break;
+                    }
+                }
+            }
+
+            // Straight up onto a ledge, needs headroom above the start
+            for height in 1..=reach.jump_height {
+                if self.is_solid((x, y - height)) {
+                    break;
+                }
+                let target = (x + dx, y - height);
+                if self.can_stand(target) {
+                    moves.push((target, Action::Jump, 10 + 10 * height as u32));
+                    break;
+                }
+            }
+
+            // Across a gap at the same height, with one tile of arc above it
+            for distance in 2..=(reach.jump_distance + 1) {
+                let target = (x + dx * distance, y);
+                let arc_clear = (0..=distance).all(|step| {
+                    let column = x + dx * step;
+                    self.column_clear(column, y - 1, y)
+                });
+                if !arc_clear {
+                    break;
+                }
+                if self.can_stand(target) {
+                    moves.push((target, Action::Jump, 10 * distance as u32 + 10));
+                    break;
+                }
+            }
+        }
+        moves
+    }
+
+    // A* over standable cells. Gives up after `max_nodes` expansions so a
+    // target that can't be reached doesn't search the whole world.
+    pub fn find_path(
+        &self,
+        from: Cell,
+        to: Cell,
+        reach: &Reach,
+        max_nodes: usize,
+    ) -> Option<Vec<(Cell, Action)>> {
+        let heuristic = |cell: Cell| estimate(cell, to);
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<Cell, (Cell, Action)> = HashMap::new();
+        let mut cost: HashMap<Cell, u32> = HashMap::new();
+        cost.insert(from, 0);
+        open.push(Reverse((heuristic(from), from)));
+
+        let mut expanded = 0;
+        while let Some(Reverse((_, cell))) = open.pop() {
+            if cell == to {
+                let mut path = Vec::new();
+                let mut current = cell;
+                while let Some(&(previous, action)) = came_from.get(&current) {
+                    path.push((current, action));
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            expanded += 1;
+            if expanded > max_nodes {
+                return None;
+            }
+            let current_cost = cost[&cell];
+            for (next, action, step_cost) in self.neighbours(cell, reach) {
+                let next_cost = current_cost + step_cost;
+                if cost.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+                cost.insert(next, next_cost);
+                came_from.insert(next, (cell, action));
+                open.push(Reverse((next_cost + heuristic(next), next)));
+            }
+        }
+        None
+    }
+}
+
+// Lower bound on the cost between two cells: every tile across costs at least
+// 10 and every tile up or down at least 5 (falls), so A* stays optimal
+fn estimate((x, y): Cell, to: Cell) -> u32 {
+    (x - to.0).unsigned_abs() * 10 + (y - to.1).unsigned_abs() * 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REACH: Reach = Reach {
+        jump_height: 1,
+        jump_distance: 2,
+        max_fall: 4,
+        can_climb: true,
+    };
+
+    // '#' is solid, 'H' a ladder, rows go down from y = 0
+    fn grid(rows: &[&str]) -> TileGrid {
+        let mut grid = TileGrid::default();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.chars().enumerate() {
+                let cell = (x as i32, y as i32);
+                grid.set_solid(cell, tile == '#');
+                grid.set_climbable(cell, tile == 'H');
+            }
+        }
+        grid
+    }
+
+    fn actions(grid: &TileGrid, from: Cell, to: Cell, reach: &Reach) -> Option<Vec<Action>> {
+        let path = grid.find_path(from, to, reach, 1000)?;
+        assert_eq!(path.last().map(|&(cell, _)| cell), Some(to));
+        Some(path.into_iter().map(|(_, action)| action).collect())
+    }
+
+    #[test]
+    fn walks_along_the_floor() {
+        let grid = grid(&[".....", "#####"]);
+        assert_eq!(
+            actions(&grid, (0, 0), (4, 0), &REACH),
+            Some(vec![Action::Walk; 4])
+        );
+    }
+
+    #[test]
+    fn jumps_onto_ledges_and_over_gaps() {
+        let ledge = grid(&["....", "..##", "####"]);
+        assert_eq!(
+            actions(&ledge, (0, 1), (2, 0), &REACH),
+            Some(vec![Action::Walk, Action::Jump])
+        );
+
+        let gap = grid(&["......", "##..##"]);
+        assert_eq!(
+            actions(&gap, (0, 0), (5, 0), &REACH),
+            Some(vec![Action::Walk, Action::Jump, Action::Walk])
+        );
+    }
+
+    #[test]
+    fn climbs_ladders_only_when_it_can() {
+        let grid = grid(&["..H...", "..H###", "..H...", "..H...", "######"]);
+        let path = actions(&grid, (0, 3), (4, 0), &REACH).unwrap();
+        assert!(path.contains(&Action::Climb), "{path:?}");
+
+        let no_climb = Reach {
+            can_climb: false,
+            ..REACH
+        };
+        assert_eq!(actions(&grid, (0, 3), (4, 0), &no_climb), None);
+    }
+
+    #[test]
+    fn falls_off_ledges() {
+        let grid = grid(&["......", "###...", "......", "......", "######"]);
+        assert_eq!(
+            actions(&grid, (0, 0), (5, 3), &REACH),
+            Some(vec![
+                Action::Walk,
+                Action::Walk,
+                Action::Fall,
+                Action::Walk,
+                Action::Walk
+            ])
+        );
+
+        let short_fall = Reach {
+            max_fall: 2,
+            ..REACH
+        };
+        assert_eq!(actions(&grid, (0, 0), (5, 3), &short_fall), None);
+    }
+
+    #[test]
+    fn gives_up_on_unreachable_targets() {
+        let grid = grid(&[".....", "...#.", "...#.", "#####"]);
+        assert_eq!(actions(&grid, (0, 2), (4, 2), &REACH), None);
+
+        let high_jump = Reach {
+            jump_height: 2,
+            ..REACH
+        };
+        assert!(actions(&grid, (0, 2), (4, 2), &high_jump).is_some());
+        assert_eq!(grid.find_path((0, 2), (4, 2), &high_jump, 1), None);
+    }
+
+    #[test]
+    fn estimate_never_overestimates_a_move() {
+        let grid = grid(&["..H...", "..H###", "..H...", "..H...", "######"]);
+        let reach = Reach {
+            jump_height: 3,
+            max_fall: 8,
+            ..REACH
+        };
+        for y in 0..5 {
+            for x in 0..6 {
+                for (next, action, cost) in grid.neighbours((x, y), &reach) {
+                    assert!(estimate((x, y), next) <= cost, "{action:?} to {next:?}");
+                }
+            }
+        }
+    }
+}
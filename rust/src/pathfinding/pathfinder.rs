@@ -0,0 +1,160 @@
+use godot::{classes::TileMapLayer, prelude::*};
+
+use crate::pathfinding::grid::{Action, Reach, TileGrid};
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct MovementProfile {
+    base: Base<Resource>,
+
+    // All in tiles
+    #[export]
+    #[init(val = 2)]
+    pub jump_height: i32,
+
+    #[export]
+    #[init(val = 2)]
+    pub jump_distance: i32,
+
+    #[export]
+    #[init(val = 4)]
+    pub max_fall: i32,
+
+    #[export]
+    pub can_climb: bool,
+}
+
+impl MovementProfile {
+    pub fn reach(&self) -> Reach {
+        Reach {
+            jump_height: self.jump_height,
+            jump_distance: self.jump_distance,
+            max_fall: self.max_fall,
+            can_climb: self.can_climb,
+        }
+    }
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct Pathfinder {
+    base: Base<Node>,
+
+    // Tiles with the "climbable" custom data set are ladders, every other tile is solid.
+    // The player climbs the same ladders through `is_climbable`
+    #[export]
+    tiles: Option<Gd<TileMapLayer>>,
+
+    // Search budget per query
+    #[export]
+    #[init(val = 4000)]
+    max_nodes: u32,
+
+    // How far down, in tiles, the start and goal of a query are moved to reach
+    // ground, so jumping or falling bodies still get a path
+    #[export]
+    #[init(val = 8)]
+    snap_depth: i32,
+
+    grid: TileGrid,
+}
+
+#[godot_api]
+impl INode for Pathfinder {
+    fn ready(&mut self) {
+        if self.tiles.is_none() {
+            godot_warn!("Pathfinder: tiles are not set");
+        }
+        self.base_mut().add_to_group("pathfinder");
+        self.rebuild();
+    }
+}
+
+#[godot_api]
+impl Pathfinder {
+    // Call whenever a tile is destroyed or placed, only that cell is re-read
+    #[func]
+    pub fn tile_changed(&mut self, cell: Vector2i) {
+        let Some(tiles) = self.tiles.clone() else {
+            return;
+        };
+        let (solid, climbable) = Self::read_cell(&tiles, cell);
+        self.grid.set_solid((cell.x, cell.y), solid);
+        self.grid.set_climbable((cell.x, cell.y), climbable);
+    }
+}
+
+impl Pathfinder {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<Pathfinder>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("pathfinder")?
+            .try_cast::<Pathfinder>()
+            .ok()
+    }
+
+    pub fn rebuild(&mut self) {
+        self.grid.clear();
+        let Some(tiles) = self.tiles.clone() else {
+            return;
+        };
+        for cell in tiles.get_used_cells().iter_shared() {
+            let (solid, climbable) = Self::read_cell(&tiles, cell);
+            self.grid.set_solid((cell.x, cell.y), solid);
+            self.grid.set_climbable((cell.x, cell.y), climbable);
+        }
+    }
+
+    fn read_cell(tiles: &Gd<TileMapLayer>, cell: Vector2i) -> (bool, bool) {
+        let Some(data) = tiles.get_cell_tile_data(cell) else {
+            return (false, false);
+        };
+        let has_layer = tiles
+            .get_tile_set()
+            .is_some_and(|tile_set| tile_set.get_custom_data_layer_by_name("climbable") >= 0);
+        let climbable = has_layer
+            && data
+                .get_custom_data("climbable")
+                .try_to::<bool>()
+                .unwrap_or(false);
+        (!climbable, climbable)
+    }
+
+    pub fn is_climbable(&self, position: Vector2) -> bool {
+        let Some(tiles) = self.tiles.as_ref() else {
+            return false;
+        };
+        let cell = tiles.local_to_map(tiles.to_local(position));
+        self.grid.is_climbable((cell.x, cell.y))
+    }
+
+    // Global positions of the tiles to pass through, with how to get to each
+    pub fn find_path(
+        &self,
+        from: Vector2,
+        to: Vector2,
+        profile: &MovementProfile,
+    ) -> Option<Vec<(Vector2, Action)>> {
+        let tiles = self.tiles.as_ref()?;
+        let to_cell = |position: Vector2| {
+            let cell = tiles.local_to_map(tiles.to_local(position));
+            let cell = (cell.x, cell.y);
+            self.grid
+                .ground_below(cell, self.snap_depth)
+                .unwrap_or(cell)
+        };
+        let path = self.grid.find_path(
+            to_cell(from),
+            to_cell(to),
+            &profile.reach(),
+            self.max_nodes as usize,
+        )?;
+        Some(
+            path.into_iter()
+                .map(|((x, y), action)| {
+                    let local = tiles.map_to_local(Vector2i::new(x, y));
+                    (tiles.to_global(local), action)
+                })
+                .collect(),
+        )
+    }
+}
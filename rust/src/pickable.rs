@@ -21,8 +21,36 @@ pub struct Pickable {
 
     #[export]
     pub quantity: u32,
+
+    // What a worn tool or weapon had left when it was dropped, 0 for a new one
+    #[export]
+    pub durability: u32,
+
+    // Gravity to restore once the player's magnet lets go
+    saved_gravity_scale: Option<f32>,
+
+    // The player whose `pick_items` currently holds this pickable
+    player: Option<Gd<Player>>,
+
+    // Seconds before despawning, 0 keeps it forever
+    #[export]
+    #[init(val = 300.0)]
+    pub lifetime: f32,
+
+    // Starts blinking this many seconds before despawning
+    #[export]
+    #[init(val = 10.0)]
+    pub blink_time: f32,
+
+    age: f32,
+
+    // Pickables only exist while their chunk is loaded, so player drops never expire
+    pub player_dropped: bool,
 }
 
+// Seconds a player drop ignores auto pickup, so it isn't taken right back
+const PICKUP_DELAY: f32 = 1.5;
+
 #[godot_api]
 impl IRigidBody2D for Pickable {
     fn ready(&mut self) {
@@ -45,6 +73,7 @@ impl IRigidBody2D for Pickable {
         {
             godot_warn!("Pickable sprite material is not a ShaderMaterial");
         }
+        self.base_mut().add_to_group("pickable");
         self.area()
             .signals()
             .body_entered()
@@ -91,6 +120,53 @@ impl Pickable {
             .expect("item must be initialized in _ready()")
     }
 
+    // Prepares a pooled or freshly instantiated pickable for a new drop
+    pub fn reset(
+        &mut self,
+        item: Gd<InventoryItem>,
+        quantity: u32,
+        durability: u32,
+        player_dropped: bool,
+    ) {
+        if let Some(icon) = item.bind().icon_path.clone() {
+            self.sprite_mut().set_texture(&icon);
+        }
+        self.sprite_mut().set_visible(true);
+        self.item = Some(item);
+        self.quantity = quantity;
+        self.durability = durability;
+        self.player_dropped = player_dropped;
+        self.age = 0.0;
+        self.release();
+        self.disable_glow();
+    }
+
+    // Returns true once the pickable should despawn. Whatever the player
+    // dropped stays as long as its chunk is loaded.
+    pub fn tick_lifetime(&mut self, delta: f32, loaded: bool) -> bool {
+        self.age += delta;
+        if self.lifetime <= 0.0 || (self.player_dropped && loaded) {
+            return false;
+        }
+        let remaining = self.lifetime - self.age;
+        if remaining <= 0.0 {
+            return true;
+        }
+        if remaining < self.blink_time {
+            let visible = (remaining * 4.0) as i32 % 2 == 0;
+            self.sprite_mut().set_visible(visible);
+        }
+        false
+    }
+
+    pub fn can_auto_pickup(&self) -> bool {
+        !self.player_dropped || self.age >= PICKUP_DELAY
+    }
+
+    pub fn leave_player(&mut self) -> Option<Gd<Player>> {
+        self.player.take()
+    }
+
     pub fn get_shader_material(&mut self) -> Option<Gd<ShaderMaterial>> {
         let material = self.sprite_mut().get_material()?;
         if !material.is_class("ShaderMaterial") {
@@ -111,24 +187,50 @@ impl Pickable {
         }
     }
 
+    pub fn pull_toward(&mut self, target: Vector2, speed: f32) {
+        if self.saved_gravity_scale.is_none() {
+            self.saved_gravity_scale = Some(self.base().get_gravity_scale());
+            self.base_mut().set_gravity_scale(0.0);
+        }
+        let direction = (target - self.base().get_global_position()).normalized();
+        self.base_mut().set_linear_velocity(direction * speed);
+    }
+
+    pub fn release(&mut self) {
+        if let Some(gravity_scale) = self.saved_gravity_scale.take() {
+            self.base_mut().set_gravity_scale(gravity_scale);
+        }
+    }
+
+    // Must be called before removing a pickable the player might be standing on,
+    // otherwise it stays in `pick_items` until the player walks away
+    pub fn detach(&mut self) {
+        self.disable_glow();
+        if let Some(mut player) = self.leave_player() {
+            player.bind_mut().pick_items.erase(&self.to_gd());
+        }
+    }
+
     fn on_body_entered(&mut self, body: Gd<Node2D>) {
         if body.is_class("Player") {
             let mut player: Gd<Player> = body.cast();
+            self.player = Some(player.clone());
             let mut player = player.bind_mut();
-            if let Some(mut item) = player.pick_items.front() {
-                item.bind_mut().disable_glow();
-            }
-            self.enable_glow();
-            player.pick_items.push_front(&self.to_gd());
+            // The player decides which one glows, see Player::update_focus
+            player.pick_items.push(&self.to_gd());
         }
     }
 
     fn on_body_exited(&mut self, body: Gd<Node2D>) {
-        if body.is_class("Player") {
-            let mut player: Gd<Player> = body.cast();
-            self.disable_glow();
-            let mut player = player.bind_mut();
-            player.pick_items.erase(&self.to_gd());
+        if !body.is_class("Player") {
+            return;
         }
+        // Already detached. Releasing to the pool disables the area, which
+        // emits this synchronously while the collecting player is still bound.
+        let Some(mut player) = self.leave_player() else {
+            return;
+        };
+        self.disable_glow();
+        player.bind_mut().pick_items.erase(&self.to_gd());
     }
 }
@@ -0,0 +1,226 @@
+use godot::{
+    classes::{RandomNumberGenerator, node::ProcessMode},
+    prelude::*,
+};
+
+use crate::{
+    inventory::item::InventoryItem, loot::LootTable, pickable::Pickable, spawner::manager::Spawner,
+};
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct PickableManager {
+    base: Base<Node>,
+
+    // Pickables of the same item closer than this are merged into one
+    #[export]
+    #[init(val = 24.0)]
+    merge_radius: f32,
+
+    // Seconds between merge passes
+    #[export]
+    #[init(val = 0.5)]
+    merge_interval: f32,
+
+    // A merged pickable never grows past this quantity
+    #[export]
+    #[init(val = 999)]
+    merge_cap: u32,
+
+    merge_timer: f32,
+
+    // Generic pickable scene, its item and sprite texture are set on spawn
+    #[export]
+    pickable_scene: Option<Gd<PackedScene>>,
+
+    // Released pickables beyond this are freed instead of pooled
+    #[export]
+    #[init(val = 64)]
+    pool_size: u32,
+
+    pool: Vec<Gd<Pickable>>,
+
+    // 0 picks a random seed, anything else makes loot rolls reproducible
+    #[export]
+    loot_seed: i64,
+
+    #[init(val = RandomNumberGenerator::new_gd())]
+    loot_rng: Gd<RandomNumberGenerator>,
+}
+
+#[godot_api]
+impl INode for PickableManager {
+    fn ready(&mut self) {
+        if self.pickable_scene.is_none() {
+            godot_warn!("PickableManager: pickable scene is not set");
+        }
+        self.base_mut().add_to_group("pickable_manager");
+        let seed = self.loot_seed;
+        self.reseed(seed);
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.tick_lifetimes(delta as f32);
+        self.merge_timer += delta as f32;
+        if self.merge_timer < self.merge_interval {
+            return;
+        }
+        self.merge_timer = 0.0;
+        self.merge_nearby();
+    }
+}
+
+impl PickableManager {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<PickableManager>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("pickable_manager")?
+            .try_cast::<PickableManager>()
+            .ok()
+    }
+
+    pub fn spawn(
+        &mut self,
+        item: &Gd<InventoryItem>,
+        quantity: u32,
+        durability: u32,
+        position: Vector2,
+        player_dropped: bool,
+    ) -> Option<Gd<Pickable>> {
+        let mut pickable = match self.pool.pop() {
+            Some(pickable) => pickable,
+            None => {
+                let scene = self.pickable_scene.as_ref()?;
+                let Some(pickable) = scene.instantiate() else {
+                    godot_error!("Failed to instantiate pickable scene");
+                    return None;
+                };
+                pickable.try_cast::<Pickable>().ok()?
+            }
+        };
+        pickable
+            .bind_mut()
+            .reset(item.clone(), quantity, durability, player_dropped);
+        if pickable.get_parent().is_none() {
+            self.base_mut().add_child(&pickable);
+        }
+        pickable.set_global_position(position);
+        pickable.set_linear_velocity(Vector2::ZERO);
+        pickable.set_process_mode(ProcessMode::INHERIT);
+        pickable.set_visible(true);
+        pickable.add_to_group("pickable");
+        Some(pickable)
+    }
+
+    pub fn reseed(&mut self, seed: i64) {
+        self.loot_seed = seed;
+        if seed == 0 {
+            self.loot_rng.randomize();
+        } else {
+            self.loot_rng.set_seed(seed as u64);
+        }
+    }
+
+    // Rolls the table and scatters whatever it gives around `position`
+    pub fn spawn_loot(&mut self, table: &Gd<LootTable>, tool_tier: u32, position: Vector2) {
+        let rng = &mut self.loot_rng;
+        let drops = table.bind().roll(tool_tier, || rng.randf());
+        for (item, quantity) in drops {
+            let Some(mut pickable) = self.spawn(&item, quantity, 0, position, false) else {
+                continue;
+            };
+            let pop = Vector2::new(self.loot_rng.randf_range(-60.0, 60.0), -120.0);
+            pickable.set_linear_velocity(pop);
+        }
+    }
+
+    // Takes the pickable out of the world, keeping it around for the next spawn
+    pub fn release(&mut self, mut pickable: Gd<Pickable>) {
+        if self.pool.contains(&pickable) {
+            return;
+        }
+        pickable.bind_mut().detach();
+        if self.pool.len() >= self.pool_size as usize {
+            pickable.queue_free();
+            return;
+        }
+        pickable.remove_from_group("pickable");
+        pickable.set_visible(false);
+        // Disabled bodies are also removed from the physics space
+        pickable.set_process_mode(ProcessMode::DISABLED);
+        self.pool.push(pickable);
+    }
+
+    fn tick_lifetimes(&mut self, delta: f32) {
+        let spawner = Spawner::find(&self.to_gd().upcast());
+        for mut pickable in self.pickables() {
+            let position = pickable.get_global_position();
+            let loaded = spawner
+                .as_ref()
+                .is_none_or(|spawner| spawner.bind().is_loaded_at(position));
+            let expired = pickable.bind_mut().tick_lifetime(delta, loaded);
+            if expired {
+                self.release(pickable);
+            }
+        }
+    }
+
+    fn pickables(&self) -> Vec<Gd<Pickable>> {
+        let Some(mut tree) = self.base().get_tree() else {
+            return Vec::new();
+        };
+        tree.get_nodes_in_group("pickable")
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<Pickable>().ok())
+            .filter(|pickable| !pickable.is_queued_for_deletion())
+            .collect()
+    }
+
+    pub fn merge_nearby(&mut self) {
+        let pickables = self.pickables();
+        let mut emptied = vec![false; pickables.len()];
+        for i in 0..pickables.len() {
+            if emptied[i] {
+                continue;
+            }
+            let mut keep = pickables[i].clone();
+            let (id, wears_out, player_dropped) = {
+                let keep = keep.bind();
+                let item = keep.item().bind();
+                (item.id(), item.max_durability > 0, keep.player_dropped)
+            };
+            // Worn tools and weapons each keep their own durability
+            if wears_out {
+                continue;
+            }
+            let position = keep.get_global_position();
+            for j in (i + 1)..pickables.len() {
+                if emptied[j] {
+                    continue;
+                }
+                let mut other = pickables[j].clone();
+                // Player drops never expire, so they only merge with each other
+                if other.get_global_position().distance_to(position) > self.merge_radius
+                    || other.bind().item().bind().id() != id
+                    || other.bind().player_dropped != player_dropped
+                {
+                    continue;
+                }
+                let kept = keep.bind().quantity;
+                if kept >= self.merge_cap {
+                    break;
+                }
+                let moved = other.bind().quantity.min(self.merge_cap - kept);
+                keep.bind_mut().quantity += moved;
+                let left = {
+                    let mut other = other.bind_mut();
+                    other.quantity -= moved;
+                    other.quantity
+                };
+                if left == 0 {
+                    self.release(other);
+                    emptied[j] = true;
+                }
+            }
+        }
+    }
+}
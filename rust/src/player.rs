@@ -1,23 +1,38 @@
 use godot::{
     classes::{
-        AnimationPlayer, CanvasLayer, CharacterBody2D, ICharacterBody2D, Input, InputEvent,
-        Marker2D, RigidBody2D, Sprite2D,
+        AnimationPlayer, Area2D, CanvasLayer, CharacterBody2D, Curve, ICharacterBody2D, Input,
+        InputEvent, Marker2D, Sprite2D,
     },
-    global::move_toward,
     prelude::*,
 };
 
 use crate::{
-    drill::Tool,
-    inventory::{inv::Inventory, ui::inv::InventoryUI},
+    health::{DamageType, Health},
+    health_ui::HealthUI,
+    inventory::{
+        inv::{Inventory, Stack},
+        item::{InventoryItem, PickupMode, UseEffect},
+        ui::inv::InventoryUI,
+    },
+    knockback::{Knockback, push_rigid_bodies},
+    movement::{Inputs, State, approach},
+    pathfinding::pathfinder::Pathfinder,
     pickable::Pickable,
+    pickable_manager::PickableManager,
+    stats::{StatModifiers, Stats},
+    status::effects::StatusEffects,
+    tool::Tool,
 };
 
-#[derive(Clone, Copy, PartialEq)]
-enum State {
-    Idle,
-    Jump,
-    Move,
+const EQUIPMENT_SLOTS: [&str; 6] = ["head", "body", "legs", "backpack", "accessory", "accessory"];
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum DeathPenalty {
+    #[default]
+    KeepInventory,
+    // Everything in the inventory (not equipment) is left where the player died
+    DropInventory,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -48,10 +63,120 @@ pub struct Player {
     #[init(val = 100.0)]
     jump_velocity: f32,
 
+    // Pixels per second squared toward `speed` while holding a direction
+    #[export]
+    #[init(val = 2400.0)]
+    acceleration: f32,
+
+    // Pixels per second squared toward standing still
+    #[export]
+    #[init(val = 3000.0)]
+    deceleration: f32,
+
+    // Scales acceleration and deceleration while airborne
+    #[export]
+    #[init(val = 0.6)]
+    air_control: f32,
+
+    // Optional, scales acceleration by the current fraction of top speed (0..1)
+    #[export]
+    acceleration_curve: Option<Gd<Curve>>,
+
     #[export]
     #[init(val = 10.0)]
     push_force: f32,
 
+    // Seconds after walking off a ledge during which jumping still works
+    #[export]
+    #[init(val = 0.1)]
+    coyote_time: f32,
+
+    // Seconds a jump press is remembered before landing
+    #[export]
+    #[init(val = 0.1)]
+    jump_buffer_time: f32,
+
+    // Upward velocity is multiplied by this when the jump is released early
+    #[export]
+    #[init(val = 0.5)]
+    jump_cut_multiplier: f32,
+
+    #[export]
+    #[init(val = 1.5)]
+    fall_gravity_multiplier: f32,
+
+    #[export]
+    #[init(val = 150.0)]
+    climb_speed: f32,
+
+    // Max fall speed while sliding down a wall
+    #[export]
+    #[init(val = 80.0)]
+    wall_slide_speed: f32,
+
+    // Horizontal speed a wall jump pushes away from the wall with
+    #[export]
+    #[init(val = 200.0)]
+    wall_jump_push: f32,
+
+    // Overlaps the terrain the player is in: water and lava ("liquid")
+    #[export]
+    terrain_detector: Option<Gd<Area2D>>,
+
+    // Landing faster than this hurts
+    #[export]
+    #[init(val = 700.0)]
+    fall_damage_speed: f32,
+
+    // Damage per pixel fallen beyond the height that reaches `fall_damage_speed`
+    #[export]
+    #[init(val = 0.1)]
+    fall_damage_per_pixel: f32,
+
+    // Highest point of the current fall, None while grounded
+    fall_start_y: Option<f32>,
+
+    peak_fall_speed: f32,
+
+    #[export]
+    health: Option<Gd<Health>>,
+
+    #[export]
+    status_effects: Option<Gd<StatusEffects>>,
+
+    #[export]
+    death_penalty: DeathPenalty,
+
+    // Seconds between dying and respawning
+    #[export]
+    #[init(val = 2.0)]
+    respawn_time: f32,
+
+    respawn_point: Vector2,
+
+    // Counts down to the respawn while dead
+    dead_timer: Option<f32>,
+
+    #[export]
+    #[init(val = 100.0)]
+    max_stamina: f32,
+
+    #[var]
+    #[init(val = 100.0)]
+    stamina: f32,
+
+    // 0 disables the magnet
+    #[export]
+    magnet_radius: f32,
+
+    #[export]
+    #[init(val = 200.0)]
+    magnet_speed: f32,
+
+    // Used for items whose pickup mode is left at Default
+    #[export]
+    auto_pickup: bool,
+
     #[export]
     flipper: Option<Gd<Node2D>>,
 
@@ -70,20 +195,85 @@ pub struct Player {
     #[export]
     inventory: Option<Gd<Inventory>>,
 
+    #[export]
+    equipment: Option<Gd<Inventory>>,
+
     #[var]
     inventory_ui: Option<Gd<InventoryUI>>,
 
     pub pick_items: Array<Gd<Pickable>>,
 
+    // The glowing pickable that `ui_pick` takes, recomputed every frame
+    focused: Option<Gd<Pickable>>,
+
+    // Focus the pickable closest to the mouse instead of the player
+    #[export]
+    focus_by_mouse: bool,
+
+    // How long `ui_pick` must be held to take everything in range
+    #[export]
+    #[init(val = 0.5)]
+    pick_all_hold_time: f32,
+
+    pick_hold: f32,
+
+    #[var]
+    armor: f32,
+
+    effective_speed: f32,
+
+    effective_jump_velocity: f32,
+
+    #[init(val = 1.0)]
+    fall_damage_multiplier: f32,
+
+    base_inventory_size: u32,
+
+    #[var]
+    selected_slot: u32,
+
+    // Temporary modifiers from consumables, with their remaining seconds
+    buffs: Vec<(Gd<StatModifiers>, f32)>,
+
     tool: Option<DynGd<Node2D, dyn Tool>>,
 
+    // Inventory slot the held tool was taken from, it wears down there
+    tool_slot: Option<(u32, Gd<InventoryItem>)>,
+
     #[init(val=State::Idle)]
     state: State,
 
+    state_time: f32,
+
+    #[init(val = f32::INFINITY)]
+    coyote_timer: f32,
+
+    #[init(val = f32::INFINITY)]
+    jump_buffer_timer: f32,
+
+    jump_cut: bool,
+
+    // Seconds left before a knockback gives control back
+    stun_timer: f32,
+
     #[init(val=Dir::Right)]
     dir: Dir,
 }
 
+#[godot_api]
+impl Player {
+    #[signal]
+    pub fn stats_changed();
+
+    #[signal]
+    pub fn reveal_area(center: Vector2, radius: f32);
+
+    #[func]
+    fn unequip_slot(&mut self, idx: u32) -> bool {
+        self.unequip(idx).is_ok()
+    }
+}
+
 #[godot_api]
 impl ICharacterBody2D for Player {
     fn ready(&mut self) {
@@ -99,10 +289,38 @@ impl ICharacterBody2D for Player {
         if self.flipper.is_none() {
             godot_warn!("Flipper node not found");
         }
+        if self.equipment.is_none() {
+            self.equipment = Some(Inventory::with_filters(&EQUIPMENT_SLOTS));
+        }
+        if let Some(inventory) = self.inventory.as_ref() {
+            self.base_inventory_size = inventory.bind().size;
+        }
+        if self.health.is_none() {
+            let health = Health::new_alloc();
+            self.base_mut().add_child(&health);
+            self.health = Some(health);
+        }
+        if self.status_effects.is_none() {
+            let status_effects = StatusEffects::new_alloc();
+            self.base_mut().add_child(&status_effects);
+            self.status_effects = Some(status_effects);
+        }
+        self.base_mut().add_to_group("player");
+        self.respawn_point = self.base().get_global_position();
+        self.refresh_stats();
         let Some(hud) = self.hud.as_mut() else {
             godot_warn!("HUD node not found");
             return;
         };
+        let health_ui_scene = load::<PackedScene>("res://scenes/ui/health.tscn");
+        match health_ui_scene.instantiate() {
+            Some(health_ui) => {
+                let mut health_ui = health_ui.cast::<HealthUI>();
+                health_ui.bind_mut().health = self.health.clone();
+                hud.add_child(&health_ui);
+            }
+            None => godot_warn!("Failed to instantiate health UI"),
+        }
         let inventory_ui_scene = load::<PackedScene>("res://scenes/ui/inventory.tscn");
         let Some(inventory_ui) = inventory_ui_scene.instantiate() else {
             godot_warn!("Failed to instantiate inventory UI");
@@ -118,22 +336,468 @@ impl ICharacterBody2D for Player {
         inventory_ui
             .signals()
             .slot_clicked()
-            .connect_other(self, Self::take_tool);
+            .connect_other(self, Self::on_slot_clicked);
 
         self.inventory_ui = Some(inventory_ui);
     }
 
     fn input(&mut self, _input: Gd<InputEvent>) {
+        if self.dead_timer.is_some() {
+            return;
+        }
         self.pick_item();
         self.inv_toggle();
+        self.inv_sort();
+        self.hotbar_select();
+        self.use_item();
+        self.drop_item();
     }
 
-    fn process(&mut self, delta: f32) {
-        self.movement(delta);
+    fn physics_process(&mut self, delta: f64) {
+        if self.update_health(delta as f32) {
+            return;
+        }
+        self.movement(delta as f32);
+        self.magnet();
+    }
+
+    fn process(&mut self, delta: f64) {
+        let delta = delta as f32;
+        self.tick_buffs(delta);
+        self.update_status_effects();
+        self.auto_pickup();
+        self.update_focus();
+        self.pick_all(delta);
     }
 }
 
 impl Player {
+    fn on_slot_clicked(&mut self, idx: u32) {
+        if idx < self.inventory().bind().hotbar_size {
+            self.select_slot(idx);
+        }
+        if self.equip(idx).is_ok() {
+            return;
+        }
+        self.take_tool(idx);
+    }
+
+    // Moves one item from the inventory slot into the matching equipment slot
+    pub fn equip(&mut self, idx: u32) -> Result<(), ()> {
+        let Some(equipment) = self.equipment.clone() else {
+            return Err(());
+        };
+        let Some(slot) = self.inventory().bind().get_slots().get(idx as usize) else {
+            return Err(());
+        };
+        let Some(item) = slot.bind().item.clone() else {
+            return Err(());
+        };
+        let durability = slot.bind().durability;
+        let Some(target) = equipment.bind().find_slot_for(&item) else {
+            return Err(());
+        };
+        // Taken out first, unequipping a backpack can shrink the inventory and
+        // move what's at `idx`
+        self.inventory_mut().bind_mut().remove_from_slot(idx, 1)?;
+
+        // Make room by sending whatever is worn there back to the inventory
+        let occupied = equipment
+            .bind()
+            .get_slots()
+            .get(target as usize)
+            .is_some_and(|slot| slot.bind().item.is_some());
+        if occupied && self.unequip(target).is_err() {
+            // A failed unequip leaves the inventory as it was, so `idx` still
+            // has room for the item
+            if let Some(mut slot) = self.inventory().bind().get_slots().get(idx as usize) {
+                let mut slot = slot.bind_mut();
+                if slot.item.is_none() {
+                    slot.item = Some(item);
+                    slot.durability = durability;
+                }
+                slot.quantity += 1;
+            }
+            return Err(());
+        }
+
+        if let Some(mut target) = equipment.bind().get_slots().get(target as usize) {
+            let mut target = target.bind_mut();
+            target.item = Some(item);
+            target.quantity = 1;
+            target.durability = durability;
+        }
+        self.on_equipment_changed();
+        Ok(())
+    }
+
+    pub fn unequip(&mut self, idx: u32) -> Result<(), ()> {
+        let Some(mut equipment) = self.equipment.clone() else {
+            return Err(());
+        };
+        let Some(mut slot) = equipment.bind().get_slots().get(idx as usize) else {
+            return Err(());
+        };
+        let Some(item) = slot.bind().item.clone() else {
+            return Err(());
+        };
+        let durability = slot.bind().durability;
+        equipment.bind_mut().remove_from_slot(idx, 1)?;
+
+        // Taking off a backpack must not lose whatever was stored in its rows
+        if self.recompute_stats().is_err()
+            || self
+                .inventory_mut()
+                .bind_mut()
+                .add_item(&item, 1, durability)
+                .is_err()
+        {
+            {
+                let mut slot = slot.bind_mut();
+                slot.item = Some(item);
+                slot.quantity = 1;
+                slot.durability = durability;
+            }
+            self.refresh_stats();
+            return Err(());
+        }
+        self.on_equipment_changed();
+        Ok(())
+    }
+
+    fn on_equipment_changed(&mut self) {
+        self.refresh_stats();
+        self.signals().stats_changed().emit();
+    }
+
+    fn total_stats(&self) -> Stats {
+        let mut stats = match self.equipment.as_ref() {
+            Some(equipment) => equipment.bind().total_modifiers(),
+            None => Stats::default(),
+        };
+        for (modifiers, _) in self.buffs.iter() {
+            stats.apply(&modifiers.bind());
+        }
+        if let Some(status_effects) = self.status_effects.as_ref() {
+            status_effects.bind().apply_modifiers(&mut stats);
+        }
+        stats
+    }
+
+    // Fails without touching the stats when the inventory can't shrink to the new size
+    fn recompute_stats(&mut self) -> Result<(), ()> {
+        let stats = self.total_stats();
+        self.resize_inventory(&stats, false)?;
+        self.apply_stats(&stats);
+        Ok(())
+    }
+
+    // For changes that can't be refused, like a buff running out. Whatever no
+    // longer fits in the shrunk inventory is dropped at the player's feet.
+    fn refresh_stats(&mut self) {
+        let stats = self.total_stats();
+        match self.resize_inventory(&stats, true) {
+            Ok(spilled) => self.drop_items(spilled),
+            Err(()) => godot_warn!("Player: failed to resize the inventory"),
+        }
+        self.apply_stats(&stats);
+        if let Some(inventory_ui) = self.inventory_ui.as_mut() {
+            inventory_ui.bind_mut().refresh();
+        }
+    }
+
+    // Returns what had to be spilled out of the inventory
+    fn resize_inventory(&mut self, stats: &Stats, spill: bool) -> Result<Vec<Stack>, ()> {
+        let base_size = self.base_inventory_size;
+        let Some(inventory) = self.inventory.as_mut() else {
+            return Ok(Vec::new());
+        };
+        let mut inventory = inventory.bind_mut();
+        let size = base_size + stats.extra_rows * inventory.row_size;
+        if size > inventory.size {
+            inventory.expand(size)?;
+        } else if size < inventory.size {
+            return inventory.shrink(size, spill);
+        }
+        Ok(Vec::new())
+    }
+
+    fn apply_stats(&mut self, stats: &Stats) {
+        self.armor = stats.armor;
+        if let Some(health) = self.health.as_mut() {
+            health.bind_mut().armor = stats.armor;
+        }
+        self.effective_speed = self.speed * stats.speed_multiplier;
+        self.effective_jump_velocity = self.jump_velocity + stats.jump_bonus;
+        self.fall_damage_multiplier = stats.fall_damage_multiplier;
+    }
+
+    fn select_slot(&mut self, idx: u32) {
+        self.selected_slot = idx;
+        if let Some(inventory_ui) = self.inventory_ui.as_mut() {
+            inventory_ui.bind_mut().select_slot(idx);
+        }
+    }
+
+    fn hotbar_select(&mut self) {
+        let input = Input::singleton();
+        let hotbar_size = self.inventory().bind().hotbar_size;
+        if hotbar_size == 0 {
+            return;
+        }
+        if input.is_action_just_pressed("ui_hotbar_next") {
+            self.select_slot((self.selected_slot + 1) % hotbar_size);
+        } else if input.is_action_just_pressed("ui_hotbar_prev") {
+            self.select_slot((self.selected_slot + hotbar_size - 1) % hotbar_size);
+        }
+    }
+
+    // Tools and weapons call this on every use, the held item breaks once it
+    // runs out of durability
+    pub fn wear_tool(&mut self) {
+        let Some((idx, item)) = self.tool_slot.clone() else {
+            return;
+        };
+        let Some(mut slot) = self.inventory().bind().get_slots().get(idx as usize) else {
+            return;
+        };
+        let broken = {
+            let mut slot = slot.bind_mut();
+            // Moved or swapped out since it was taken in hand
+            if slot.item.as_ref() != Some(&item) || item.bind().max_durability == 0 {
+                return;
+            }
+            slot.durability = item.bind().durability_or_new(slot.durability) - 1;
+            slot.durability == 0
+        };
+        if broken
+            && self
+                .inventory_mut()
+                .bind_mut()
+                .remove_from_slot(idx, 1)
+                .is_ok()
+        {
+            self.tool_slot = None;
+            if let Some(tool) = self.tool.take() {
+                tool.into_gd().queue_free();
+            }
+        }
+        self.inventory_ui_mut().bind_mut().refresh();
+    }
+
+    // Ranged weapons pay for each shot through this
+    pub fn take_ammo(&mut self, ammo: &Gd<InventoryItem>) -> bool {
+        if self.inventory_mut().bind_mut().take(ammo, 1).is_err() {
+            return false;
+        }
+        self.inventory_ui_mut().bind_mut().refresh();
+        true
+    }
+
+    fn use_item(&mut self) {
+        if !Input::singleton().is_action_just_pressed("ui_use") {
+            return;
+        }
+        let idx = self.selected_slot;
+        if !self.can_use(idx) {
+            return;
+        }
+        let Ok(item) = self.inventory_mut().bind_mut().consume(idx) else {
+            return;
+        };
+        self.inventory_ui_mut().bind_mut().refresh();
+
+        let item = item.bind();
+        let amount = item.effect_amount;
+        match item.use_effect {
+            UseEffect::None => {}
+            UseEffect::Heal => {
+                if let Some(health) = self.health.as_mut() {
+                    health.bind_mut().heal(amount);
+                }
+            }
+            UseEffect::RestoreStamina => {
+                self.stamina = (self.stamina + amount).min(self.max_stamina);
+            }
+            UseEffect::Buff => {
+                let Some(modifiers) = item.modifiers.clone() else {
+                    return;
+                };
+                self.buffs.push((modifiers, item.effect_duration));
+                self.refresh_stats();
+                self.signals().stats_changed().emit();
+            }
+            UseEffect::RevealMap => {
+                let center = self.base().get_global_position();
+                self.signals().reveal_area().emit(center, amount);
+            }
+            UseEffect::Cure => {
+                if let Some(status_effects) = self.status_effects.as_mut() {
+                    status_effects.bind_mut().cure(&item.cures);
+                }
+            }
+        }
+    }
+
+    // A cure is only used up when there's something to cure
+    fn can_use(&self, idx: u32) -> bool {
+        let Some(item) = self
+            .inventory()
+            .bind()
+            .get_slots()
+            .get(idx as usize)
+            .and_then(|slot| slot.bind().item.clone())
+        else {
+            return false;
+        };
+        let item = item.bind();
+        if item.use_effect != UseEffect::Cure {
+            return true;
+        }
+        self.status_effects
+            .as_ref()
+            .is_some_and(|status_effects| status_effects.bind().has(&item.cures))
+    }
+
+    // Drops the whole selected hotbar stack at the player's feet
+    fn drop_item(&mut self) {
+        if !Input::singleton().is_action_just_pressed("ui_drop") {
+            return;
+        }
+        let Some(mut manager) = PickableManager::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        let idx = self.selected_slot;
+        let Some(slot) = self.inventory().bind().get_slots().get(idx as usize) else {
+            return;
+        };
+        let (item, quantity, durability) = {
+            let slot = slot.bind();
+            let Some(item) = slot.item.clone() else {
+                return;
+            };
+            (item, slot.quantity, slot.durability)
+        };
+        if self
+            .inventory_mut()
+            .bind_mut()
+            .remove_from_slot(idx, quantity)
+            .is_err()
+        {
+            return;
+        }
+        self.inventory_ui_mut().bind_mut().refresh();
+        let position = self.base().get_global_position();
+        manager
+            .bind_mut()
+            .spawn(&item, quantity, durability, position, true);
+    }
+
+    // Returns true while dead, so movement and input are skipped
+    fn update_health(&mut self, delta: f32) -> bool {
+        let Some(health) = self.health.clone() else {
+            return false;
+        };
+        if let Some(timer) = self.dead_timer.as_mut() {
+            *timer -= delta;
+            if *timer <= 0.0 {
+                self.respawn();
+            }
+            return true;
+        }
+        if health.bind().is_dead() {
+            self.die();
+            return true;
+        }
+        false
+    }
+
+    fn die(&mut self) {
+        self.dead_timer = Some(self.respawn_time);
+        self.base_mut().set_velocity(Vector2::ZERO);
+        if self.anim_player().has_animation("death") {
+            self.anim_player_mut().set_current_animation("death");
+        }
+        if self.death_penalty == DeathPenalty::DropInventory {
+            self.drop_inventory();
+        }
+    }
+
+    fn respawn(&mut self) {
+        self.dead_timer = None;
+        self.fall_start_y = None;
+        self.peak_fall_speed = 0.0;
+        self.stun_timer = 0.0;
+        let respawn_point = self.respawn_point;
+        self.base_mut().set_global_position(respawn_point);
+        self.base_mut().set_velocity(Vector2::ZERO);
+        if let Some(health) = self.health.as_mut() {
+            health.bind_mut().revive();
+        }
+        if let Some(status_effects) = self.status_effects.as_mut() {
+            status_effects.bind_mut().clear();
+        }
+        // Force the enter hook so the idle animation replaces the death one
+        self.state = State::Fall;
+        self.set_state(State::Idle);
+    }
+
+    fn drop_inventory(&mut self) {
+        // Kept rather than lost when there's nowhere to drop it
+        if PickableManager::find(&self.to_gd().upcast()).is_none() {
+            return;
+        }
+        let dropped = self.inventory_mut().bind_mut().take_all();
+        self.drop_items(dropped);
+        self.inventory_ui_mut().bind_mut().refresh();
+    }
+
+    fn drop_items(&mut self, stacks: Vec<Stack>) {
+        if stacks.is_empty() {
+            return;
+        }
+        let Some(mut manager) = PickableManager::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        let position = self.base().get_global_position();
+        for stack in stacks {
+            manager.bind_mut().spawn(
+                &stack.item,
+                stack.quantity,
+                stack.durability,
+                position,
+                true,
+            );
+        }
+    }
+
+    fn tick_buffs(&mut self, delta: f32) {
+        if self.buffs.is_empty() {
+            return;
+        }
+        for (_, remaining) in self.buffs.iter_mut() {
+            *remaining -= delta;
+        }
+        let len = self.buffs.len();
+        self.buffs.retain(|(_, remaining)| *remaining > 0.0);
+        if self.buffs.len() != len {
+            self.refresh_stats();
+            self.signals().stats_changed().emit();
+        }
+    }
+
+    // Status effects like slows change stats whenever they come and go
+    fn update_status_effects(&mut self) {
+        let Some(mut status_effects) = self.status_effects.clone() else {
+            return;
+        };
+        if !status_effects.bind_mut().take_changed() {
+            return;
+        }
+        self.refresh_stats();
+        self.signals().stats_changed().emit();
+    }
+
     fn take_tool(&mut self, idx: u32) {
         let slot = {
             let inventory = self.inventory().bind();
@@ -172,48 +836,136 @@ impl Player {
             .unwrap()
             .add_child(&tool.to_godot());
         self.tool = Some(tool);
+        self.tool_slot = slot.item.clone().map(|item| (idx, item));
     }
 
     fn pick_item(&mut self) {
         if Input::singleton().is_action_just_pressed("ui_pick") {
-            let len = self.pick_items.len();
-            if len == 0 {
-                return;
-            }
-            let Some(pickable_item) = self.pick_items.front() else {
+            let Some(pickable_item) = self.focused.clone() else {
                 return;
             };
-            let pickable_item = pickable_item.bind();
-            let item = pickable_item.item();
-            let quantity = pickable_item.quantity;
-            let result = {
-                let Some(inventory) = self.inventory.as_mut() else {
-                    return;
-                };
-                let mut inventory = inventory.bind_mut();
-                inventory.add_item(item, quantity)
-            };
-            self.inventory_ui_mut().bind_mut().refresh();
+            self.collect(pickable_item);
+        }
+    }
 
-            // If item was successfully added to the inventory
-            if result.is_ok() {
-                if let Some(mut pickable_item) = self.pick_items.pop_front() {
-                    pickable_item.queue_free();
-                }
-                if let Some(mut pickable_item) = self.pick_items.front() {
-                    let mut pickable_item = pickable_item.bind_mut();
-                    pickable_item.enable_glow();
-                }
-                return;
-            }
-            // If either item was not added to the inventory at all or only partially
-            let Some(mut pickable_item) = self.pick_items.front() else {
-                return;
+    fn pick_all(&mut self, delta: f32) {
+        if !Input::singleton().is_action_pressed("ui_pick") {
+            self.pick_hold = 0.0;
+            return;
+        }
+        self.pick_hold += delta;
+        if self.pick_hold < self.pick_all_hold_time {
+            return;
+        }
+        let in_range: Vec<Gd<Pickable>> = self.pick_items.iter_shared().collect();
+        for pickable in in_range {
+            self.collect(pickable);
+        }
+    }
+
+    fn update_focus(&mut self) {
+        let origin = if self.focus_by_mouse {
+            self.base().get_global_mouse_position()
+        } else {
+            self.base().get_global_position()
+        };
+        let nearest = self.pick_items.iter_shared().min_by(|a, b| {
+            let a = a.get_global_position().distance_squared_to(origin);
+            let b = b.get_global_position().distance_squared_to(origin);
+            a.total_cmp(&b)
+        });
+        if nearest == self.focused {
+            return;
+        }
+        if let Some(mut old) = self.focused.take()
+            && old.is_instance_valid()
+        {
+            old.bind_mut().disable_glow();
+        }
+        if let Some(mut new) = nearest.clone() {
+            new.bind_mut().enable_glow();
+        }
+        self.focused = nearest;
+    }
+
+    // Returns true once the whole stack made it into the inventory
+    fn collect(&mut self, mut pickable_item: Gd<Pickable>) -> bool {
+        let result = {
+            let pickable = pickable_item.bind();
+            let Some(inventory) = self.inventory.as_mut() else {
+                return false;
             };
-            let mut pickable_item = pickable_item.bind_mut();
-            if let Err(quantity) = result {
-                pickable_item.quantity = quantity;
+            let mut inventory = inventory.bind_mut();
+            inventory.add_item(pickable.item(), pickable.quantity, pickable.durability)
+        };
+        self.inventory_ui_mut().bind_mut().refresh();
+
+        // If either item was not added to the inventory at all or only partially
+        if let Err(quantity) = result {
+            pickable_item.bind_mut().quantity = quantity;
+            return false;
+        }
+
+        if self.focused.as_ref() == Some(&pickable_item) {
+            self.focused = None;
+        }
+        // Already bound here, so the pickable must not detach itself from us
+        pickable_item.bind_mut().leave_player();
+        self.pick_items.erase(&pickable_item);
+        match PickableManager::find(&self.to_gd().upcast()) {
+            Some(mut manager) => manager.bind_mut().release(pickable_item),
+            None => pickable_item.queue_free(),
+        }
+        true
+    }
+
+    fn wants_auto_pickup(&self, pickable: &Gd<Pickable>) -> bool {
+        let pickable = pickable.bind();
+        if !pickable.can_auto_pickup() {
+            return false;
+        }
+        match pickable.item().bind().pickup_mode {
+            PickupMode::Auto => true,
+            PickupMode::Manual => false,
+            PickupMode::Default => self.auto_pickup,
+        }
+    }
+
+    fn auto_pickup(&mut self) {
+        let touching: Vec<Gd<Pickable>> = self
+            .pick_items
+            .iter_shared()
+            .filter(|pickable| self.wants_auto_pickup(pickable))
+            .collect();
+        for pickable in touching {
+            self.collect(pickable);
+        }
+    }
+
+    // Pulls auto-pickup items in range toward the player while there's room for them
+    fn magnet(&mut self) {
+        if self.magnet_radius <= 0.0 {
+            return;
+        }
+        let position = self.base().get_global_position();
+        let Some(mut tree) = self.base().get_tree() else {
+            return;
+        };
+        for node in tree.get_nodes_in_group("pickable").iter_shared() {
+            let Ok(mut pickable) = node.try_cast::<Pickable>() else {
+                continue;
             };
+            let in_range =
+                pickable.get_global_position().distance_to(position) <= self.magnet_radius;
+            let pull = in_range
+                && self.wants_auto_pickup(&pickable)
+                && self.inventory().bind().can_fit(pickable.bind().item());
+            let mut pickable = pickable.bind_mut();
+            if pull {
+                pickable.pull_toward(position, self.magnet_speed);
+            } else {
+                pickable.release();
+            }
         }
     }
 
@@ -227,71 +979,225 @@ impl Player {
         }
     }
 
+    fn inv_sort(&mut self) {
+        if Input::singleton().is_action_just_pressed("ui_sort") {
+            let Some(inventory_ui) = self.inventory_ui.as_mut() else {
+                return;
+            };
+            let mut inventory_ui = inventory_ui.bind_mut();
+            inventory_ui.sort();
+        }
+    }
+
+    // Takes over the velocity until the stun wears off
+    pub fn knockback(&mut self, knockback: Knockback) {
+        self.stun_timer = self.stun_timer.max(knockback.stun);
+        self.base_mut().set_velocity(knockback.velocity());
+    }
+
     fn movement(&mut self, delta: f32) {
         let input = Input::singleton();
-        let mut velocity = self.base().get_velocity();
-        if input.is_action_just_pressed("ui_accept") && self.base().is_on_floor() {
-            let jump_velocity = self.jump_velocity;
-            velocity.y = -jump_velocity;
-            self.set_state(State::Jump)
+        self.stun_timer = (self.stun_timer - delta).max(0.0);
+        // Controls are ignored while stunned, the knockback carries the player
+        let stunned = self.stun_timer > 0.0;
+        let control = if stunned { 0.0 } else { 1.0 };
+        let on_floor = self.base().is_on_floor();
+        if on_floor {
+            self.coyote_timer = 0.0;
         } else {
-            let direction = input.get_axis("ui_left", "ui_right");
-            self.set_dir(direction);
-            let speed = self.speed;
-            velocity.x = direction * speed;
-            if direction != 0.0 {
-                if self.base().is_on_floor() {
-                    self.set_state(State::Move);
-                }
-            } else {
-                let x = move_toward(velocity.x.as_f64(), 0.0f64, speed.as_f64());
-                velocity.x = x as f32;
-                if self.base().is_on_floor() {
-                    self.set_state(State::Idle);
+            self.coyote_timer += delta;
+        }
+        if input.is_action_just_pressed("ui_accept") {
+            self.jump_buffer_timer = 0.0;
+        } else {
+            self.jump_buffer_timer += delta;
+        }
+        let inputs = Inputs {
+            on_floor,
+            on_wall: self.base().is_on_wall_only(),
+            on_ladder: self.on_ladder(),
+            direction: input.get_axis("ui_left", "ui_right") * control,
+            climb: input.get_axis("ui_up", "ui_down") * control,
+            jump: !stunned && self.jump_buffer_timer <= self.jump_buffer_time,
+            coyote: self.coyote_timer <= self.coyote_time,
+            velocity: self.base().get_velocity(),
+        };
+        self.state_time += delta;
+        let next = self.state.next(&inputs, self.state_time);
+        self.set_state(next);
+        self.set_dir(inputs.direction);
+
+        // Enter hooks may have changed the velocity (jumps)
+        let mut velocity = self.base().get_velocity();
+        let speed = self.effective_speed;
+        match self.state {
+            _ if stunned => {}
+            State::Climb => {
+                let target = inputs.direction * speed * 0.5;
+                velocity.x = approach(velocity.x, target, self.acceleration, delta);
+                velocity.y = inputs.climb * self.climb_speed;
+                let climbing = if inputs.climb != 0.0 { 1.0 } else { 0.0 };
+                self.anim_player_mut().set_speed_scale(climbing);
+            }
+            // Keep the push away from the wall instead of steering
+            State::WallJump => {}
+            _ => {
+                let target = inputs.direction * speed;
+                let mut rate = if inputs.direction != 0.0 {
+                    self.acceleration * self.acceleration_scale(velocity.x, speed)
+                } else {
+                    self.deceleration
+                };
+                if !inputs.on_floor {
+                    rate *= self.air_control;
                 }
+                velocity.x = approach(velocity.x, target, rate, delta);
             }
         }
-        if !self.base().is_on_floor() {
-            let gravity = self.base().get_gravity() * delta;
+        // Letting go of jump early cuts the rise short
+        if self.state == State::Jump
+            && !stunned
+            && !self.jump_cut
+            && velocity.y < 0.0
+            && !input.is_action_pressed("ui_accept")
+        {
+            velocity.y *= self.jump_cut_multiplier;
+            self.jump_cut = true;
+        }
+        if self.state != State::Climb && !self.base().is_on_floor() {
+            let mut gravity = self.base().get_gravity() * delta;
+            if velocity.y > 0.0 {
+                gravity *= self.fall_gravity_multiplier;
+            }
             velocity += gravity
         }
+        if self.state == State::WallSlide {
+            velocity.y = velocity.y.min(self.wall_slide_speed);
+        }
         self.base_mut().set_velocity(velocity);
         self.base_mut().move_and_slide();
+        self.track_fall(velocity.y);
+        let push_force = self.push_force;
+        push_rigid_bodies(&mut self.base_mut(), push_force);
+    }
 
-        for i in 0..self.base().get_slide_collision_count() {
-            let c = self.base_mut().get_slide_collision(i);
-            match c {
-                Some(c) => {
-                    let Some(col) = c.get_collider() else {
-                        continue;
-                    };
-                    if !col.is_class("RigidBody2D") {
-                        continue;
-                    }
-                    let mut obj: Gd<RigidBody2D> = col.cast();
-                    let normal = -c.get_normal();
-                    if normal.y.abs() > 0.7 {
-                        continue;
-                    }
-                    let impulse =
-                        Vector2::new(normal.x * self.push_force, normal.y * self.push_force);
-                    obj.apply_central_impulse_ex().impulse(impulse).done();
-                    //obj.apply_impulse(impulse);
-                }
-                None => continue,
-            }
+    fn acceleration_scale(&self, velocity_x: f32, speed: f32) -> f32 {
+        let Some(curve) = self.acceleration_curve.as_ref() else {
+            return 1.0;
+        };
+        if speed <= 0.0 {
+            return 1.0;
         }
+        curve.sample((velocity_x.abs() / speed).clamp(0.0, 1.0))
     }
 
     fn set_state(&mut self, state: State) {
         if self.state == state {
             return;
         }
+        let previous = self.state;
+        self.exit_state(previous);
         self.state = state;
+        self.state_time = 0.0;
+        self.enter_state(state, previous);
+    }
+
+    fn enter_state(&mut self, state: State, previous: State) {
+        if matches!(state, State::Jump | State::WallJump) {
+            // Each press and each ledge only ever gives one jump
+            self.jump_buffer_timer = f32::INFINITY;
+            self.coyote_timer = f32::INFINITY;
+            self.jump_cut = false;
+        }
         match state {
-            State::Idle => self.anim_player_mut().set_current_animation("idel"),
-            State::Jump => self.anim_player_mut().set_current_animation("jump"),
-            State::Move => self.anim_player_mut().set_current_animation("move"),
+            // Still rising from a wall jump, its impulse was already given
+            State::Jump if previous == State::WallJump => {}
+            State::Jump => {
+                let mut velocity = self.base().get_velocity();
+                velocity.y = -self.effective_jump_velocity;
+                self.base_mut().set_velocity(velocity);
+            }
+            State::WallJump => {
+                let away = self.base().get_wall_normal().x;
+                let velocity =
+                    Vector2::new(away * self.wall_jump_push, -self.effective_jump_velocity);
+                self.base_mut().set_velocity(velocity);
+            }
+            _ => {}
+        }
+        let anim_player = self.anim_player_mut();
+        if anim_player.has_animation(state.animation()) {
+            anim_player.set_current_animation(state.animation());
+        } else {
+            anim_player.set_current_animation(state.fallback_animation());
+        }
+    }
+
+    fn exit_state(&mut self, state: State) {
+        // Climbing pauses the animation while standing still on the ladder
+        if state == State::Climb {
+            self.anim_player_mut().set_speed_scale(1.0);
+        }
+    }
+
+    // Ladders are tiles, read from the same grid enemies path over
+    fn on_ladder(&self) -> bool {
+        let position = self.base().get_global_position();
+        Pathfinder::find(&self.to_gd().upcast())
+            .is_some_and(|pathfinder| pathfinder.bind().is_climbable(position))
+    }
+
+    fn touching(&self, group: &str) -> bool {
+        let Some(detector) = self.terrain_detector.as_ref() else {
+            return false;
+        };
+        let areas = detector.get_overlapping_areas();
+        let bodies = detector.get_overlapping_bodies();
+        areas.iter_shared().any(|area| area.is_in_group(group))
+            || bodies.iter_shared().any(|body| body.is_in_group(group))
+    }
+
+    // Call after move_and_slide with the velocity it was given, since
+    // landing zeroes the body's own velocity
+    fn track_fall(&mut self, fall_speed: f32) {
+        let on_floor = self.base().is_on_floor();
+        let y = self.base().get_global_position().y;
+        // Ladders and wall slides are controlled descents
+        if matches!(self.state, State::Climb | State::WallSlide)
+            || (on_floor && self.fall_start_y.is_none())
+        {
+            self.fall_start_y = None;
+            self.peak_fall_speed = 0.0;
+            return;
+        }
+        if !on_floor {
+            let start = self.fall_start_y.map_or(y, |start| start.min(y));
+            self.fall_start_y = Some(start);
+            self.peak_fall_speed = self.peak_fall_speed.max(fall_speed);
+            return;
+        }
+
+        let distance = y - self.fall_start_y.take().unwrap_or(y);
+        let peak = std::mem::take(&mut self.peak_fall_speed);
+        self.apply_fall_damage(peak, distance);
+    }
+
+    fn apply_fall_damage(&mut self, peak: f32, distance: f32) {
+        if peak < self.fall_damage_speed || self.touching("liquid") {
+            return;
+        }
+        // Height needed to reach the damage speed, falls shorter than that are free
+        let gravity = self.base().get_gravity().y * self.fall_gravity_multiplier;
+        let safe_distance = if gravity > 0.0 {
+            self.fall_damage_speed.powi(2) / (2.0 * gravity)
+        } else {
+            0.0
+        };
+        let damage = (distance - safe_distance).max(0.0)
+            * self.fall_damage_per_pixel
+            * self.fall_damage_multiplier;
+        if let Some(health) = self.health.as_mut() {
+            health.bind_mut().damage(damage, DamageType::Fall);
         }
     }
 
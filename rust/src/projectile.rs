@@ -0,0 +1,159 @@
+use godot::{
+    classes::{Area2D, IArea2D},
+    prelude::*,
+};
+
+use crate::{
+    enemy::body::Enemy,
+    health::{DamageType, Health},
+    knockback::{self, Knockback},
+    tile_health::TileHealth,
+};
+
+// Moved and recycled by the ProjectileManager, which polls `step` rather than
+// the projectile releasing itself from its own collision callback
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct Projectile {
+    base: Base<Area2D>,
+
+    // Pixels per second
+    pub velocity: Vector2,
+
+    // Multiplies the world gravity, 0 flies straight
+    #[export]
+    gravity_scale: f32,
+
+    #[export]
+    #[init(val = 10.0)]
+    damage: f32,
+
+    #[export]
+    #[init(val = 100.0)]
+    knockback: f32,
+
+    // Seconds whatever it hits can't steer
+    #[export]
+    #[init(val = 0.1)]
+    knockback_stun: f32,
+
+    // Seconds before it disappears on its own
+    #[export]
+    #[init(val = 3.0)]
+    lifetime: f32,
+
+    // Bodies it can pass through before stopping, terrain always stops it
+    #[export]
+    pierce: u32,
+
+    // Damage dealt to the tile it stops on, 0 leaves tiles alone
+    #[export]
+    tile_damage: f32,
+
+    age: f32,
+
+    // Already hit, so piercing doesn't hit the same body twice
+    hit: Vec<InstanceId>,
+
+    // Whoever fired it, never hit by their own projectile
+    source: Option<InstanceId>,
+
+    // Of the weapon that fired it, for the loot of whatever it breaks or kills
+    tool_tier: u32,
+
+    // Waiting for the manager to take it back
+    spent: bool,
+}
+
+#[godot_api]
+impl IArea2D for Projectile {
+    fn ready(&mut self) {
+        self.signals()
+            .body_entered()
+            .connect_self(Self::on_body_entered);
+    }
+}
+
+// Needed for `signals()`
+#[godot_api]
+impl Projectile {}
+
+impl Projectile {
+    pub fn launch(&mut self, velocity: Vector2, source: Option<InstanceId>, tool_tier: u32) {
+        self.velocity = velocity;
+        self.source = source;
+        self.tool_tier = tool_tier;
+        self.age = 0.0;
+        self.hit.clear();
+        self.spent = false;
+        self.base_mut().set_rotation(velocity.angle());
+    }
+
+    // Returns true once the projectile is done and should be released
+    pub fn step(&mut self, delta: f32, gravity: Vector2) -> bool {
+        self.age += delta;
+        if self.spent || self.age >= self.lifetime {
+            return true;
+        }
+        self.velocity += gravity * self.gravity_scale * delta;
+        let velocity = self.velocity;
+        let position = self.base().get_global_position();
+        self.base_mut()
+            .set_global_position(position + velocity * delta);
+        self.base_mut().set_rotation(velocity.angle());
+        false
+    }
+
+    fn on_body_entered(&mut self, body: Gd<Node2D>) {
+        if self.spent
+            || Some(body.instance_id()) == self.source
+            || self.hit.contains(&body.instance_id())
+        {
+            return;
+        }
+        let Some(mut health) = Health::of(&body.clone().upcast()) else {
+            // Terrain, or anything else without health
+            self.hit_tile();
+            self.spent = true;
+            return;
+        };
+        self.hit.push(body.instance_id());
+        if health.bind_mut().damage(self.damage, DamageType::Physical) {
+            if let Ok(mut enemy) = body.clone().try_cast::<Enemy>() {
+                enemy.bind_mut().hit_with(self.tool_tier);
+            }
+            knockback::apply(
+                body,
+                Knockback {
+                    direction: self.velocity,
+                    strength: self.knockback,
+                    stun: self.knockback_stun,
+                },
+            );
+        }
+        if self.hit.len() > self.pierce as usize {
+            self.spent = true;
+        }
+    }
+
+    fn hit_tile(&mut self) {
+        if self.tile_damage <= 0.0 {
+            return;
+        }
+        let Some(mut tile_health) = TileHealth::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        // The projectile may stop just short of the tile it ran into
+        let position = self.base().get_global_position();
+        let ahead = position + self.velocity.normalized_or_zero() * 4.0;
+        let cell = {
+            let tile_health = tile_health.bind();
+            tile_health.cell_at(position).or(tile_health.cell_at(ahead))
+        };
+        if let Some(cell) = cell {
+            tile_health
+                .bind_mut()
+                .damage(cell, self.tile_damage, self.tool_tier);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use godot::{
+    classes::{ProjectSettings, node::ProcessMode},
+    prelude::*,
+};
+
+use crate::projectile::Projectile;
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct ProjectileManager {
+    base: Base<Node>,
+
+    // Released projectiles beyond this, per scene, are freed instead of pooled
+    #[export]
+    #[init(val = 32)]
+    pool_size: u32,
+
+    // Keyed by the scene file each projectile came from
+    pool: HashMap<GString, Vec<Gd<Projectile>>>,
+
+    gravity: Vector2,
+}
+
+#[godot_api]
+impl INode for ProjectileManager {
+    fn ready(&mut self) {
+        self.base_mut().add_to_group("projectile_manager");
+        let settings = ProjectSettings::singleton();
+        let strength = settings
+            .get_setting("physics/2d/default_gravity")
+            .try_to::<f32>()
+            .unwrap_or(980.0);
+        let direction = settings
+            .get_setting("physics/2d/default_gravity_vector")
+            .try_to::<Vector2>()
+            .unwrap_or(Vector2::DOWN);
+        self.gravity = direction * strength;
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let gravity = self.gravity;
+        for mut projectile in self.projectiles() {
+            let done = projectile.bind_mut().step(delta as f32, gravity);
+            if done {
+                self.release(projectile);
+            }
+        }
+    }
+}
+
+impl ProjectileManager {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<ProjectileManager>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("projectile_manager")?
+            .try_cast::<ProjectileManager>()
+            .ok()
+    }
+
+    pub fn spawn(
+        &mut self,
+        scene: &Gd<PackedScene>,
+        position: Vector2,
+        velocity: Vector2,
+        source: Option<InstanceId>,
+        tool_tier: u32,
+    ) -> Option<Gd<Projectile>> {
+        let pooled = self
+            .pool
+            .get_mut(&scene.get_path())
+            .and_then(|pool| pool.pop());
+        let mut projectile = match pooled {
+            Some(projectile) => projectile,
+            None => {
+                let Some(projectile) = scene.instantiate() else {
+                    godot_error!("Failed to instantiate projectile scene");
+                    return None;
+                };
+                projectile.try_cast::<Projectile>().ok()?
+            }
+        };
+        if projectile.get_parent().is_none() {
+            self.base_mut().add_child(&projectile);
+        }
+        projectile.set_global_position(position);
+        projectile.bind_mut().launch(velocity, source, tool_tier);
+        projectile.set_process_mode(ProcessMode::INHERIT);
+        projectile.set_visible(true);
+        projectile.add_to_group("projectile");
+        Some(projectile)
+    }
+
+    // Takes the projectile out of the world, keeping it around for the next spawn
+    pub fn release(&mut self, mut projectile: Gd<Projectile>) {
+        let pool = self
+            .pool
+            .entry(projectile.get_scene_file_path())
+            .or_default();
+        if pool.contains(&projectile) {
+            return;
+        }
+        if pool.len() >= self.pool_size as usize {
+            projectile.queue_free();
+            return;
+        }
+        projectile.remove_from_group("projectile");
+        projectile.set_visible(false);
+        // Disabled areas are also removed from the physics space
+        projectile.set_process_mode(ProcessMode::DISABLED);
+        pool.push(projectile);
+    }
+
+    fn projectiles(&self) -> Vec<Gd<Projectile>> {
+        let Some(mut tree) = self.base().get_tree() else {
+            return Vec::new();
+        };
+        tree.get_nodes_in_group("projectile")
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<Projectile>().ok())
+            .filter(|projectile| !projectile.is_queued_for_deletion())
+            .collect()
+    }
+}
@@ -0,0 +1,144 @@
+use godot::{
+    classes::{IStaticBody2D, Sprite2D, StaticBody2D},
+    prelude::*,
+};
+
+use crate::{
+    loot::LootTable,
+    map::{ResourcesMem, Vector2Mem},
+    pickable_manager::PickableManager,
+};
+
+#[derive(GodotClass)]
+#[class(init, base=StaticBody2D)]
+pub struct ResourceNode {
+    base: Base<StaticBody2D>,
+
+    // Frames go from full to nearly depleted
+    #[export]
+    sprite: Option<Gd<Sprite2D>>,
+
+    #[export]
+    loot_table: Option<Gd<LootTable>>,
+
+    #[export]
+    #[init(val = 5)]
+    max_quantity: u8,
+
+    #[export]
+    min_tool_tier: u32,
+
+    // Seconds to grow back one harvest, 0 removes the node once it's empty
+    #[export]
+    regrow_time: f32,
+
+    #[var]
+    quantity: u8,
+
+    // Set when the quantity came from a save instead of `max_quantity`
+    restored: bool,
+
+    regrow_timer: f32,
+
+    saved_collision_layer: u32,
+}
+
+#[godot_api]
+impl IStaticBody2D for ResourceNode {
+    fn ready(&mut self) {
+        if self.sprite.is_none() {
+            godot_warn!("ResourceNode must have a sprite");
+        }
+        if self.loot_table.is_none() {
+            godot_warn!("ResourceNode has no loot table");
+        }
+        if !self.restored {
+            self.quantity = self.max_quantity;
+        }
+        self.base_mut().add_to_group("resource_node");
+        self.saved_collision_layer = self.base().get_collision_layer();
+        self.update_visual();
+        if self.quantity == 0 {
+            self.deplete();
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        if self.regrow_time <= 0.0 || self.quantity >= self.max_quantity {
+            return;
+        }
+        self.regrow_timer += delta as f32;
+        if self.regrow_timer < self.regrow_time {
+            return;
+        }
+        self.regrow_timer = 0.0;
+        if self.quantity == 0 {
+            let layer = self.saved_collision_layer;
+            self.base_mut().set_collision_layer(layer);
+            self.base_mut().set_visible(true);
+        }
+        self.quantity += 1;
+        self.update_visual();
+    }
+}
+
+impl ResourceNode {
+    pub fn from_mem(mem: &ResourcesMem) -> Option<Gd<ResourceNode>> {
+        let scene = try_load::<PackedScene>(mem.scene.as_str()).ok()?;
+        let mut node = scene.instantiate()?.try_cast::<ResourceNode>().ok()?;
+        {
+            let mut node = node.bind_mut();
+            node.quantity = mem.quantity;
+            node.restored = true;
+        }
+        node.set_global_position(Vector2::new(mem.pos.x, mem.pos.y));
+        Some(node)
+    }
+
+    pub fn to_mem(&self) -> ResourcesMem {
+        let pos = self.base().get_global_position();
+        ResourcesMem {
+            pos: Vector2Mem { x: pos.x, y: pos.y },
+            scene: self.base().get_scene_file_path().to_string(),
+            quantity: self.quantity,
+        }
+    }
+
+    pub fn harvest(&mut self, tool_tier: u32) {
+        if self.quantity == 0 || tool_tier < self.min_tool_tier {
+            return;
+        }
+        self.quantity -= 1;
+        if let Some(table) = self.loot_table.clone()
+            && let Some(mut manager) = PickableManager::find(&self.to_gd().upcast())
+        {
+            let position = self.base().get_global_position();
+            manager.bind_mut().spawn_loot(&table, tool_tier, position);
+        }
+        self.update_visual();
+        if self.quantity == 0 {
+            self.deplete();
+        }
+    }
+
+    fn deplete(&mut self) {
+        if self.regrow_time <= 0.0 {
+            self.base_mut().queue_free();
+            return;
+        }
+        self.regrow_timer = 0.0;
+        self.base_mut().set_collision_layer(0);
+        self.base_mut().set_visible(false);
+    }
+
+    fn update_visual(&mut self) {
+        let max_quantity = self.max_quantity.max(1) as f32;
+        let depleted = 1.0 - self.quantity as f32 / max_quantity;
+        let Some(sprite) = self.sprite.as_mut() else {
+            return;
+        };
+        let frames = sprite.get_hframes() * sprite.get_vframes();
+        let frame = (depleted * (frames - 1) as f32).round() as i32;
+        sprite.set_frame(frame.clamp(0, frames - 1));
+    }
+}
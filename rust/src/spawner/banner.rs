@@ -0,0 +1,84 @@
+use godot::{
+    classes::{Control, IControl, Label},
+    prelude::*,
+};
+
+use crate::spawner::manager::Spawner;
+
+// HUD announcements for horde nights
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct HordeBanner {
+    base: Base<Control>,
+
+    #[export]
+    label: Option<Gd<Label>>,
+
+    #[export]
+    spawner: Option<Gd<Spawner>>,
+
+    // Seconds an announcement stays up
+    #[export]
+    #[init(val = 4.0)]
+    display_time: f32,
+
+    timer: f32,
+}
+
+#[godot_api]
+impl IControl for HordeBanner {
+    fn ready(&mut self) {
+        if self.label.is_none() {
+            godot_warn!("HordeBanner: label is not set");
+        }
+        self.base_mut().hide();
+        let Some(spawner) = self.spawner.clone() else {
+            godot_warn!("HordeBanner: spawner is not set");
+            return;
+        };
+        spawner
+            .signals()
+            .horde_started()
+            .connect_other(self, Self::on_horde_started);
+        spawner
+            .signals()
+            .wave_started()
+            .connect_other(self, Self::on_wave_started);
+        spawner
+            .signals()
+            .horde_ended()
+            .connect_other(self, Self::on_horde_ended);
+    }
+
+    fn process(&mut self, delta: f64) {
+        if self.timer <= 0.0 {
+            return;
+        }
+        self.timer -= delta as f32;
+        if self.timer <= 0.0 {
+            self.base_mut().hide();
+        }
+    }
+}
+
+impl HordeBanner {
+    fn announce(&mut self, text: &str) {
+        if let Some(label) = self.label.as_mut() {
+            label.set_text(text);
+        }
+        self.timer = self.display_time;
+        self.base_mut().show();
+    }
+
+    fn on_horde_started(&mut self, announcement: GString) {
+        self.announce(&announcement.to_string());
+    }
+
+    fn on_wave_started(&mut self, wave: u32, waves: u32) {
+        self.announce(&format!("Wave {wave}/{waves}"));
+    }
+
+    fn on_horde_ended(&mut self, _night: u32) {
+        self.announce("Dawn breaks, the horde retreats");
+    }
+}
@@ -0,0 +1,48 @@
+use godot::{
+    classes::{Area2D, IArea2D, PhysicsPointQueryParameters2D},
+    prelude::*,
+};
+
+// Marks a region of the world as a biome for the spawn tables
+#[derive(GodotClass)]
+#[class(init, base=Area2D)]
+pub struct BiomeArea {
+    base: Base<Area2D>,
+
+    #[export]
+    pub name: StringName,
+}
+
+#[godot_api]
+impl IArea2D for BiomeArea {
+    fn ready(&mut self) {
+        if self.name.is_empty() {
+            godot_warn!("BiomeArea: name is not set");
+        }
+        // Only ever queried, nothing needs to collide with it
+        self.base_mut().set_monitoring(false);
+    }
+}
+
+impl BiomeArea {
+    // Name of the biome at `position`, empty outside of every biome
+    pub fn at(node: &Gd<Node2D>, position: Vector2) -> StringName {
+        let Some(mut world) = node.get_world_2d() else {
+            return StringName::default();
+        };
+        let Some(mut space) = world.get_direct_space_state() else {
+            return StringName::default();
+        };
+        let mut query = PhysicsPointQueryParameters2D::new_gd();
+        query.set_position(position);
+        query.set_collide_with_areas(true);
+        query.set_collide_with_bodies(false);
+        space
+            .intersect_point(&query)
+            .iter_shared()
+            .filter_map(|hit| hit.get("collider")?.try_to::<Gd<BiomeArea>>().ok())
+            .map(|biome| biome.bind().name.clone())
+            .next()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,95 @@
+use godot::prelude::*;
+
+use crate::{loot::LootTable, map::HordeMem, spawner::table::SpawnTable};
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct HordeWave {
+    base: Base<Resource>,
+
+    #[export]
+    pub table: Option<Gd<SpawnTable>>,
+
+    #[export]
+    #[init(val = 5)]
+    pub count: u32,
+
+    // Seconds after the previous wave finished spawning
+    #[export]
+    #[init(val = 20.0)]
+    pub delay: f32,
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct HordeNight {
+    base: Base<Resource>,
+
+    // Shown on the HUD when the horde starts
+    #[export]
+    #[init(val = GString::from("The dead are rising"))]
+    pub announcement: GString,
+
+    // Spawned in order, later waves are expected to be bigger
+    #[export]
+    pub waves: Array<Gd<HordeWave>>,
+
+    // Every this many nights is a horde night, 0 only leaves it to chance
+    #[export]
+    #[init(val = 7)]
+    pub every: u32,
+
+    // Chance any other night turns into a horde night
+    #[export]
+    pub chance: f32,
+
+    // Dropped at the base once the horde is survived until dawn
+    #[export]
+    pub reward: Option<Gd<LootTable>>,
+}
+
+impl HordeNight {
+    pub fn is_scheduled(&self, night: u32) -> bool {
+        self.every > 0 && night > 0 && night.is_multiple_of(self.every)
+    }
+}
+
+// Progress through the waves of the current horde
+#[derive(Clone, Copy, Debug)]
+pub struct HordeState {
+    pub night: u32,
+    pub wave: usize,
+    // Enemies of the current wave already spawned
+    pub spawned: u32,
+    // Seconds since the previous wave finished spawning
+    pub timer: f32,
+}
+
+impl HordeState {
+    pub fn new(night: u32) -> Self {
+        Self {
+            night,
+            wave: 0,
+            spawned: 0,
+            timer: 0.0,
+        }
+    }
+
+    pub fn from_mem(mem: &HordeMem) -> Self {
+        Self {
+            night: mem.night,
+            wave: mem.wave as usize,
+            spawned: mem.spawned,
+            timer: mem.timer,
+        }
+    }
+
+    pub fn to_mem(self) -> HordeMem {
+        HordeMem {
+            night: self.night,
+            wave: self.wave as u32,
+            spawned: self.spawned,
+            timer: self.timer,
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+
+use godot::{
+    classes::{RandomNumberGenerator, TileMapLayer},
+    prelude::*,
+};
+
+use crate::{
+    day_cycle::DayCycle,
+    enemy::body::Enemy,
+    map::{EntitiesMem, EventsMem},
+    pickable_manager::PickableManager,
+    spawner::{
+        biome::BiomeArea,
+        horde::{HordeNight, HordeState},
+        table::{SpawnConditions, SpawnTable},
+    },
+};
+
+// Chunk coordinates, in chunks rather than tiles
+pub type Chunk = (i32, i32);
+
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct Spawner {
+    base: Base<Node>,
+
+    #[export]
+    table: Option<Gd<SpawnTable>>,
+
+    // Enemies spawn on empty cells with a free cell above and a solid one below
+    #[export]
+    tiles: Option<Gd<TileMapLayer>>,
+
+    // In tiles
+    #[export]
+    #[init(val = 32)]
+    chunk_size: i32,
+
+    // Chunks this many chunks away from the player's count as loaded
+    #[export]
+    #[init(val = 2)]
+    load_radius: i32,
+
+    // Most live enemies a single chunk can hold
+    #[export]
+    #[init(val = 3)]
+    chunk_cap: u32,
+
+    // Seconds between spawn passes
+    #[export]
+    #[init(val = 2.0)]
+    spawn_interval: f32,
+
+    // Positions tried per spawn pass
+    #[export]
+    #[init(val = 8)]
+    spawn_attempts: u32,
+
+    // Pixels around the visible area where nothing spawns
+    #[export]
+    #[init(val = 64.0)]
+    view_margin: f32,
+
+    // Enemies further than this from the player are stored and freed
+    #[export]
+    #[init(val = 1600.0)]
+    despawn_distance: f32,
+
+    // Tile row of the surface, depth is counted from here
+    #[export]
+    surface_row: i32,
+
+    // Tiles below the surface daylight still reaches
+    #[export]
+    #[init(val = 20.0)]
+    light_depth: f32,
+
+    // Nodes in the "light_source" group light up this many pixels around them
+    #[export]
+    #[init(val = 128.0)]
+    light_source_radius: f32,
+
+    // Replaces the regular spawns with waves on horde nights
+    #[export]
+    horde: Option<Gd<HordeNight>>,
+
+    spawn_timer: f32,
+
+    rolled_night: Option<u32>,
+
+    horde_state: Option<HordeState>,
+
+    // Despawned entities waiting for the player to come back
+    stored: HashMap<Chunk, Vec<EntitiesMem>>,
+
+    #[init(val = RandomNumberGenerator::new_gd())]
+    rng: Gd<RandomNumberGenerator>,
+}
+
+#[godot_api]
+impl INode for Spawner {
+    fn ready(&mut self) {
+        if self.table.is_none() {
+            godot_warn!("Spawner: spawn table is not set");
+        }
+        if self.tiles.is_none() {
+            godot_warn!("Spawner: tiles are not set");
+        }
+        self.rng.randomize();
+        self.base_mut().add_to_group("spawner");
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.spawn_timer += delta as f32;
+        if self.spawn_timer < self.spawn_interval {
+            return;
+        }
+        let elapsed = std::mem::take(&mut self.spawn_timer);
+        if self.tiles.is_none() {
+            return;
+        }
+        let Some(player) = self.player() else {
+            return;
+        };
+        let player_position = player.get_global_position();
+        self.despawn_far(player_position);
+        self.restore_near(player_position);
+        self.update_horde(elapsed, player_position);
+        if self.horde_state.is_some() {
+            return;
+        }
+        for _ in 0..self.spawn_attempts {
+            self.try_spawn(player_position);
+        }
+    }
+}
+
+#[godot_api]
+impl Spawner {
+    #[signal]
+    pub fn horde_started(announcement: GString);
+
+    // Both counted from 1
+    #[signal]
+    pub fn wave_started(wave: u32, waves: u32);
+
+    #[signal]
+    pub fn horde_ended(night: u32);
+}
+
+impl Spawner {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<Spawner>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("spawner")?
+            .try_cast::<Spawner>()
+            .ok()
+    }
+
+    fn player(&self) -> Option<Gd<Node2D>> {
+        let mut tree = self.base().get_tree()?;
+        tree.get_first_node_in_group("player")?
+            .try_cast::<Node2D>()
+            .ok()
+    }
+
+    fn enemies(&self) -> Vec<Gd<Enemy>> {
+        let Some(mut tree) = self.base().get_tree() else {
+            return Vec::new();
+        };
+        tree.get_nodes_in_group("enemy")
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<Enemy>().ok())
+            .collect()
+    }
+
+    fn cell_of(&self, position: Vector2) -> Vector2i {
+        let tiles = self.tiles();
+        tiles.local_to_map(tiles.to_local(position))
+    }
+
+    fn chunk_of(&self, position: Vector2) -> Chunk {
+        let cell = self.cell_of(position);
+        let size = self.chunk_size.max(1);
+        (cell.x.div_euclid(size), cell.y.div_euclid(size))
+    }
+
+    // Whether `position` is in a chunk around the player. Everything counts as
+    // loaded while there's no player or tiles to measure chunks with.
+    pub fn is_loaded_at(&self, position: Vector2) -> bool {
+        let Some(player) = self.player() else {
+            return true;
+        };
+        if self.tiles.is_none() {
+            return true;
+        }
+        let player_chunk = self.chunk_of(player.get_global_position());
+        self.is_loaded(self.chunk_of(position), player_chunk)
+    }
+
+    fn is_loaded(&self, chunk: Chunk, player_chunk: Chunk) -> bool {
+        (chunk.0 - player_chunk.0).abs() <= self.load_radius
+            && (chunk.1 - player_chunk.1).abs() <= self.load_radius
+    }
+
+    // What the camera currently shows, in global coordinates
+    fn view_rect(&self) -> Option<Rect2> {
+        let viewport = self.base().get_viewport()?;
+        let visible = viewport.get_visible_rect();
+        let to_world = viewport.get_canvas_transform().affine_inverse();
+        let top_left = to_world * visible.position;
+        let bottom_right = to_world * visible.end();
+        Some(Rect2::new(top_left, bottom_right - top_left).grow(self.view_margin))
+    }
+
+    // Enemies stored away in unloaded chunks
+    pub fn stored_mem(&self) -> Vec<EntitiesMem> {
+        self.stored.values().flatten().cloned().collect()
+    }
+
+    // Replaces the stored enemies, they come back once their chunk is loaded
+    pub fn store_mem(&mut self, mems: Vec<EntitiesMem>) {
+        self.stored.clear();
+        for mem in mems {
+            let chunk = self.chunk_of(Vector2::new(mem.pos.x, mem.pos.y));
+            self.stored.entry(chunk).or_default().push(mem);
+        }
+    }
+
+    pub fn load_mem(&mut self, mem: &EventsMem) {
+        self.rolled_night = mem.rolled_night;
+        self.horde_state = mem.horde.as_ref().map(HordeState::from_mem);
+    }
+
+    pub fn to_mem(&self) -> EventsMem {
+        EventsMem {
+            rolled_night: self.rolled_night,
+            horde: self.horde_state.map(HordeState::to_mem),
+        }
+    }
+
+    // Where horde enemies head for, the player when there's no base
+    fn home(&self) -> Option<Gd<Node2D>> {
+        let mut tree = self.base().get_tree()?;
+        tree.get_first_node_in_group("player_base")
+            .and_then(|node| node.try_cast::<Node2D>().ok())
+            .or_else(|| self.player())
+    }
+
+    fn update_horde(&mut self, elapsed: f32, player_position: Vector2) {
+        let Some(horde) = self.horde.clone() else {
+            return;
+        };
+        let Some(day_cycle) = DayCycle::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        let (night, is_night) = {
+            let day_cycle = day_cycle.bind();
+            (day_cycle.night(), day_cycle.is_night())
+        };
+        match self.horde_state {
+            Some(state) if !is_night => self.end_horde(&horde, state.night),
+            Some(_) => self.spawn_waves(&horde, elapsed, player_position),
+            None if is_night && self.rolled_night != Some(night) => {
+                self.rolled_night = Some(night);
+                let starts = {
+                    let horde = horde.bind();
+                    horde.is_scheduled(night) || self.rng.randf() < horde.chance
+                };
+                if starts {
+                    self.horde_state = Some(HordeState::new(night));
+                    let announcement = horde.bind().announcement.clone();
+                    self.signals().horde_started().emit(&announcement);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn spawn_waves(&mut self, horde: &Gd<HordeNight>, elapsed: f32, player_position: Vector2) {
+        let Some(mut state) = self.horde_state else {
+            return;
+        };
+        let waves: Vec<_> = horde.bind().waves.iter_shared().collect();
+        state.timer += elapsed;
+        // Every wave is out, hold on until dawn
+        let Some(wave) = waves.get(state.wave) else {
+            self.horde_state = Some(state);
+            return;
+        };
+        let (table, count, delay) = {
+            let wave = wave.bind();
+            (wave.table.clone(), wave.count, wave.delay)
+        };
+        if state.spawned == 0 && state.timer < delay {
+            self.horde_state = Some(state);
+            return;
+        }
+
+        let home = self.home();
+        let spawned_before = state.spawned;
+        if let Some(table) = table {
+            for _ in 0..self.spawn_attempts {
+                if state.spawned >= count {
+                    break;
+                }
+                if self.spawn_horde_enemy(&table, player_position, home.clone()) {
+                    state.spawned += 1;
+                }
+            }
+        } else {
+            state.spawned = count;
+        }
+        if spawned_before == 0 && state.spawned > 0 {
+            self.signals()
+                .wave_started()
+                .emit(state.wave as u32 + 1, waves.len() as u32);
+        }
+        if state.spawned >= count {
+            state.wave += 1;
+            state.spawned = 0;
+            state.timer = 0.0;
+        }
+        self.horde_state = Some(state);
+    }
+
+    fn spawn_horde_enemy(
+        &mut self,
+        table: &Gd<SpawnTable>,
+        player_position: Vector2,
+        home: Option<Gd<Node2D>>,
+    ) -> bool {
+        let Some((cell, position)) = self.pick_position(player_position, false) else {
+            return false;
+        };
+        let conditions = self.conditions_at(cell, position);
+        let Some(scene) = table.bind().pick(&conditions, &mut self.rng) else {
+            return false;
+        };
+        let Some(node) = self.spawn(&scene, position) else {
+            return false;
+        };
+        if let Ok(mut enemy) = node.try_cast::<Enemy>() {
+            enemy.bind_mut().set_goal(home);
+        }
+        true
+    }
+
+    fn end_horde(&mut self, horde: &Gd<HordeNight>, night: u32) {
+        self.horde_state = None;
+        // Survivors go back to roaming, so they can despawn again
+        for mut enemy in self.enemies() {
+            enemy.bind_mut().set_goal(None);
+        }
+        let reward = horde.bind().reward.clone();
+        if let (Some(reward), Some(home)) = (reward, self.home())
+            && let Some(mut manager) = PickableManager::find(&self.to_gd().upcast())
+        {
+            manager
+                .bind_mut()
+                // Earned by surviving, not with a tool
+                .spawn_loot(&reward, u32::MAX, home.get_global_position());
+        }
+        self.signals().horde_ended().emit(night);
+    }
+
+    fn despawn_far(&mut self, player_position: Vector2) {
+        for mut enemy in self.enemies() {
+            // Horde enemies keep coming no matter how far they are
+            if enemy.bind().has_goal() {
+                continue;
+            }
+            let position = enemy.get_global_position();
+            if position.distance_to(player_position) <= self.despawn_distance {
+                continue;
+            }
+            let mem = enemy.bind().to_mem();
+            // Enemies that don't come from a scene file can't be restored
+            if mem.scene.is_empty() {
+                continue;
+            }
+            let chunk = self.chunk_of(position);
+            self.stored.entry(chunk).or_default().push(mem);
+            enemy.queue_free();
+        }
+    }
+
+    fn restore_near(&mut self, player_position: Vector2) {
+        let player_chunk = self.chunk_of(player_position);
+        let chunks: Vec<Chunk> = self
+            .stored
+            .keys()
+            .copied()
+            .filter(|&chunk| self.is_loaded(chunk, player_chunk))
+            .collect();
+        for chunk in chunks {
+            let Some(mems) = self.stored.remove(&chunk) else {
+                continue;
+            };
+            let (near, far): (Vec<EntitiesMem>, Vec<EntitiesMem>) =
+                mems.into_iter().partition(|mem| {
+                    Vector2::new(mem.pos.x, mem.pos.y).distance_to(player_position)
+                        <= self.despawn_distance
+                });
+            if !far.is_empty() {
+                self.stored.insert(chunk, far);
+            }
+            for mem in near {
+                if let Some(enemy) = Enemy::from_mem(&mem) {
+                    self.base_mut().add_child(&enemy);
+                }
+            }
+        }
+    }
+
+    // Live enemies per chunk
+    fn population(&self) -> HashMap<Chunk, u32> {
+        let mut population = HashMap::new();
+        for enemy in self.enemies() {
+            *population
+                .entry(self.chunk_of(enemy.get_global_position()))
+                .or_default() += 1;
+        }
+        population
+    }
+
+    fn is_spawnable(&self, cell: Vector2i) -> bool {
+        let tiles = self.tiles();
+        let empty = |cell: Vector2i| tiles.get_cell_source_id(cell) == -1;
+        empty(cell) && empty(cell + Vector2i::UP) && !empty(cell + Vector2i::DOWN)
+    }
+
+    fn light_at(&self, position: Vector2, depth: i32) -> f32 {
+        let Some(mut tree) = self.base().get_tree() else {
+            return 1.0;
+        };
+        let lit = tree
+            .get_nodes_in_group("light_source")
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<Node2D>().ok())
+            .any(|light| {
+                light.get_global_position().distance_to(position) <= self.light_source_radius
+            });
+        if lit {
+            return 1.0;
+        }
+        let daylight = DayCycle::find(&self.to_gd().upcast())
+            .map_or(1.0, |day_cycle| day_cycle.bind().daylight());
+        let reach = (1.0 - depth as f32 / self.light_depth.max(1.0)).clamp(0.0, 1.0);
+        daylight * reach
+    }
+
+    fn conditions_at(&self, cell: Vector2i, position: Vector2) -> SpawnConditions {
+        let depth = cell.y - self.surface_row;
+        let night = DayCycle::find(&self.to_gd().upcast())
+            .is_some_and(|day_cycle| day_cycle.bind().is_night());
+        SpawnConditions {
+            depth,
+            biome: BiomeArea::at(&self.tiles().clone().upcast(), position),
+            light: self.light_at(position, depth),
+            night,
+        }
+    }
+
+    // A random spawnable cell in a loaded chunk, out of view and, when
+    // `capped`, under the chunk's population cap
+    fn pick_position(
+        &mut self,
+        player_position: Vector2,
+        capped: bool,
+    ) -> Option<(Vector2i, Vector2)> {
+        let player_chunk = self.chunk_of(player_position);
+        let radius = self.load_radius;
+        let size = self.chunk_size.max(1);
+        let chunk = (
+            player_chunk.0 + self.rng.randi_range(-radius, radius),
+            player_chunk.1 + self.rng.randi_range(-radius, radius),
+        );
+        if capped && self.population().get(&chunk).copied().unwrap_or(0) >= self.chunk_cap {
+            return None;
+        }
+        let cell = Vector2i::new(
+            chunk.0 * size + self.rng.randi_range(0, size - 1),
+            chunk.1 * size + self.rng.randi_range(0, size - 1),
+        );
+        if !self.is_spawnable(cell) {
+            return None;
+        }
+        let tiles = self.tiles();
+        let position = tiles.to_global(tiles.map_to_local(cell));
+        if self
+            .view_rect()
+            .is_some_and(|view| view.contains_point(position))
+        {
+            return None;
+        }
+        Some((cell, position))
+    }
+
+    fn try_spawn(&mut self, player_position: Vector2) {
+        let Some(table) = self.table.clone() else {
+            return;
+        };
+        let Some((cell, position)) = self.pick_position(player_position, true) else {
+            return;
+        };
+        let conditions = self.conditions_at(cell, position);
+        let Some(scene) = table.bind().pick(&conditions, &mut self.rng) else {
+            return;
+        };
+        self.spawn(&scene, position);
+    }
+
+    pub fn spawn(&mut self, scene: &Gd<PackedScene>, position: Vector2) -> Option<Gd<Node2D>> {
+        let mut node = scene.instantiate()?.try_cast::<Node2D>().ok()?;
+        node.set_global_position(position);
+        self.base_mut().add_child(&node);
+        Some(node)
+    }
+
+    fn tiles(&self) -> &Gd<TileMapLayer> {
+        self.tiles
+            .as_ref()
+            .expect("tiles must be initialized in _ready()")
+    }
+}
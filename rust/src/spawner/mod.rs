@@ -0,0 +1,5 @@
+pub mod banner;
+pub mod biome;
+pub mod horde;
+pub mod manager;
+pub mod table;
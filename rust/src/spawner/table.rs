@@ -0,0 +1,99 @@
+use godot::{classes::RandomNumberGenerator, prelude::*};
+
+// What the world looks like where a spawn is being attempted
+pub struct SpawnConditions {
+    // Tiles below the surface, negative above it
+    pub depth: i32,
+    pub biome: StringName,
+    // 0 is pitch black, 1 is full daylight
+    pub light: f32,
+    pub night: bool,
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct SpawnEntry {
+    base: Base<Resource>,
+
+    #[export]
+    pub scene: Option<Gd<PackedScene>>,
+
+    // Relative to the other entries that can spawn at the same place
+    #[export]
+    #[init(val = 1.0)]
+    pub weight: f32,
+
+    // Both in tiles below the surface
+    #[export]
+    #[init(val = -1000)]
+    pub min_depth: i32,
+
+    #[export]
+    #[init(val = 1000)]
+    pub max_depth: i32,
+
+    // Empty spawns in any biome
+    #[export]
+    pub biome: StringName,
+
+    // Only spawns where it's at most this bright
+    #[export]
+    #[init(val = 1.0)]
+    pub max_light: f32,
+
+    #[export]
+    pub night_only: bool,
+}
+
+impl SpawnEntry {
+    pub fn allows(&self, conditions: &SpawnConditions) -> bool {
+        (self.min_depth..=self.max_depth).contains(&conditions.depth)
+            && (self.biome.is_empty() || self.biome == conditions.biome)
+            && conditions.light <= self.max_light
+            && (!self.night_only || conditions.night)
+    }
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct SpawnTable {
+    base: Base<Resource>,
+
+    #[export]
+    pub entries: Array<Gd<SpawnEntry>>,
+}
+
+impl SpawnTable {
+    pub fn pick(
+        &self,
+        conditions: &SpawnConditions,
+        rng: &mut Gd<RandomNumberGenerator>,
+    ) -> Option<Gd<PackedScene>> {
+        let entries: Vec<Gd<SpawnEntry>> = self
+            .entries
+            .iter_shared()
+            .filter(|entry| entry.bind().allows(conditions) && entry.bind().scene.is_some())
+            .collect();
+        let total: f32 = entries
+            .iter()
+            .map(|entry| entry.bind().weight.max(0.0))
+            .sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut pick = rng.randf() * total;
+        let entry = entries
+            .iter()
+            .find(|entry| {
+                let weight = entry.bind().weight.max(0.0);
+                if pick < weight {
+                    return true;
+                }
+                pick -= weight;
+                false
+            })
+            .or(entries.last())?;
+        entry.bind().scene.clone()
+    }
+}
@@ -0,0 +1,84 @@
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct StatModifiers {
+    base: Base<Resource>,
+
+    #[export]
+    pub armor: f32,
+
+    #[export]
+    #[init(val = 1.0)]
+    pub speed_multiplier: f32,
+
+    #[export]
+    pub jump_bonus: f32,
+
+    #[export]
+    pub extra_rows: u32,
+
+    // Boots and the like, 0.5 halves fall damage
+    #[export]
+    #[init(val = 1.0)]
+    pub fall_damage_multiplier: f32,
+}
+
+impl StatModifiers {
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.armor != 0.0 {
+            lines.push(format!("{:+} armor", self.armor));
+        }
+        if self.speed_multiplier != 1.0 {
+            lines.push(format!(
+                "{:+.0}% move speed",
+                (self.speed_multiplier - 1.0) * 100.0
+            ));
+        }
+        if self.jump_bonus != 0.0 {
+            lines.push(format!("{:+} jump", self.jump_bonus));
+        }
+        if self.fall_damage_multiplier != 1.0 {
+            lines.push(format!(
+                "{:+.0}% fall damage",
+                (self.fall_damage_multiplier - 1.0) * 100.0
+            ));
+        }
+        if self.extra_rows > 0 {
+            lines.push(format!("+{} inventory rows", self.extra_rows));
+        }
+        lines
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stats {
+    pub armor: f32,
+    pub speed_multiplier: f32,
+    pub jump_bonus: f32,
+    pub extra_rows: u32,
+    pub fall_damage_multiplier: f32,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            armor: 0.0,
+            speed_multiplier: 1.0,
+            jump_bonus: 0.0,
+            extra_rows: 0,
+            fall_damage_multiplier: 1.0,
+        }
+    }
+}
+
+impl Stats {
+    pub fn apply(&mut self, modifiers: &StatModifiers) {
+        self.armor += modifiers.armor;
+        self.speed_multiplier *= modifiers.speed_multiplier;
+        self.jump_bonus += modifiers.jump_bonus;
+        self.extra_rows += modifiers.extra_rows;
+        self.fall_damage_multiplier *= modifiers.fall_damage_multiplier;
+    }
+}
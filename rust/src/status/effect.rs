@@ -0,0 +1,65 @@
+use godot::prelude::*;
+
+use crate::{health::DamageType, stats::StatModifiers};
+
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum Stacking {
+    // Applying it again tops the duration back up
+    #[default]
+    Refresh,
+    // Applying it again adds its duration to what's left
+    Extend,
+    // Every application adds a stack up to `max_stacks` and restarts the
+    // duration, damage and modifiers apply once per stack
+    Stack,
+    // Applying it again while it's active does nothing
+    Ignore,
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct StatusEffect {
+    base: Base<Resource>,
+
+    // Effects with the same name count as the same effect for stacking and cures
+    #[export]
+    pub name: StringName,
+
+    #[export]
+    pub damage_per_second: f32,
+
+    #[export]
+    #[init(val = DamageType::Poison)]
+    pub damage_type: DamageType,
+
+    // Seconds between damage ticks
+    #[export]
+    #[init(val = 1.0)]
+    pub tick_interval: f32,
+
+    // Seconds, 0 lasts until cured
+    #[export]
+    #[init(val = 5.0)]
+    pub duration: f32,
+
+    // Applied on top of the entity's other stats while active
+    #[export]
+    pub modifiers: Option<Gd<StatModifiers>>,
+
+    #[export]
+    pub stacking: Stacking,
+
+    #[export]
+    #[init(val = 1)]
+    pub max_stacks: u32,
+
+    // Shader parameter set to the effect's intensity, 0 to 1, while active
+    #[export]
+    pub shader_param: StringName,
+
+    // Takes over once this one runs out, so an infection keeps getting worse
+    // until it's cured
+    #[export]
+    pub worsens_into: Option<Gd<StatusEffect>>,
+}
@@ -0,0 +1,202 @@
+use godot::{
+    classes::{CanvasItem, ShaderMaterial},
+    prelude::*,
+};
+
+use crate::{
+    health::Health,
+    stats::Stats,
+    status::effect::{Stacking, StatusEffect},
+};
+
+struct Active {
+    effect: Gd<StatusEffect>,
+    stacks: u32,
+    remaining: f32,
+    tick_timer: f32,
+}
+
+// Status effects currently on an entity, a child of it like its Health
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct StatusEffects {
+    base: Base<Node>,
+
+    // Effect shader parameters are set on this item's ShaderMaterial
+    #[export]
+    shader_target: Option<Gd<CanvasItem>>,
+
+    active: Vec<Active>,
+
+    // Modifiers changed since the owner last recomputed its stats
+    changed: bool,
+}
+
+#[godot_api]
+impl INode for StatusEffects {
+    fn process(&mut self, delta: f64) {
+        self.tick(delta as f32);
+    }
+}
+
+impl StatusEffects {
+    pub fn of(node: &Gd<Node>) -> Option<Gd<StatusEffects>> {
+        node.get_children()
+            .iter_shared()
+            .find_map(|child| child.try_cast::<StatusEffects>().ok())
+    }
+
+    fn find(&self, name: &StringName) -> Option<usize> {
+        self.active
+            .iter()
+            .position(|active| active.effect.bind().name == *name)
+    }
+
+    pub fn has(&self, name: &StringName) -> bool {
+        self.find(name).is_some()
+    }
+
+    pub fn apply(&mut self, effect: Gd<StatusEffect>) {
+        let (name, duration, stacking, max_stacks) = {
+            let effect = effect.bind();
+            let duration = if effect.duration > 0.0 {
+                effect.duration
+            } else {
+                f32::INFINITY
+            };
+            (
+                effect.name.clone(),
+                duration,
+                effect.stacking,
+                effect.max_stacks.max(1),
+            )
+        };
+        let Some(idx) = self.find(&name) else {
+            self.active.push(Active {
+                effect,
+                stacks: 1,
+                remaining: duration,
+                tick_timer: 0.0,
+            });
+            self.changed = true;
+            self.refresh_shader(self.active.len() - 1);
+            return;
+        };
+        let active = &mut self.active[idx];
+        match stacking {
+            Stacking::Refresh => active.remaining = active.remaining.max(duration),
+            Stacking::Extend => active.remaining += duration,
+            Stacking::Stack => {
+                if active.stacks < max_stacks {
+                    active.stacks += 1;
+                    self.changed = true;
+                }
+                active.remaining = duration;
+            }
+            Stacking::Ignore => {}
+        }
+        self.refresh_shader(idx);
+    }
+
+    // Removes every effect with the name, returns false if there was none
+    pub fn cure(&mut self, name: &StringName) -> bool {
+        let mut cured = false;
+        while let Some(idx) = self.find(name) {
+            self.remove(idx);
+            cured = true;
+        }
+        cured
+    }
+
+    pub fn clear(&mut self) {
+        while !self.active.is_empty() {
+            self.remove(self.active.len() - 1);
+        }
+    }
+
+    // Every active modifier once per stack
+    pub fn apply_modifiers(&self, stats: &mut Stats) {
+        for active in self.active.iter() {
+            let Some(modifiers) = active.effect.bind().modifiers.clone() else {
+                continue;
+            };
+            for _ in 0..active.stacks {
+                stats.apply(&modifiers.bind());
+            }
+        }
+    }
+
+    // Polled by the owner instead of a signal, like Health::take_knockback
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn tick(&mut self, delta: f32) {
+        let health = self
+            .base()
+            .get_parent()
+            .and_then(|parent| Health::of(&parent));
+        let mut expired = Vec::new();
+        for (idx, active) in self.active.iter_mut().enumerate() {
+            active.remaining -= delta;
+            active.tick_timer += delta;
+            let (damage_per_second, damage_type, interval) = {
+                let effect = active.effect.bind();
+                (
+                    effect.damage_per_second,
+                    effect.damage_type,
+                    effect.tick_interval.max(0.1),
+                )
+            };
+            while active.tick_timer >= interval {
+                active.tick_timer -= interval;
+                let damage = damage_per_second * interval * active.stacks as f32;
+                if let Some(mut health) = health.clone() {
+                    health.bind_mut().tick_damage(damage, damage_type);
+                }
+            }
+            if active.remaining <= 0.0 {
+                expired.push(idx);
+            }
+        }
+        for idx in expired.into_iter().rev() {
+            let worse = self.active[idx].effect.bind().worsens_into.clone();
+            self.remove(idx);
+            if let Some(worse) = worse {
+                self.apply(worse);
+            }
+        }
+    }
+
+    fn remove(&mut self, idx: usize) {
+        let active = self.active.remove(idx);
+        self.changed = true;
+        let param = active.effect.bind().shader_param.clone();
+        self.set_shader_param(&param, 0.0);
+    }
+
+    fn refresh_shader(&mut self, idx: usize) {
+        let active = &self.active[idx];
+        let (param, max_stacks) = {
+            let effect = active.effect.bind();
+            (effect.shader_param.clone(), effect.max_stacks.max(1))
+        };
+        let intensity = active.stacks as f32 / max_stacks as f32;
+        self.set_shader_param(&param, intensity);
+    }
+
+    fn set_shader_param(&mut self, param: &StringName, value: f32) {
+        if param.is_empty() {
+            return;
+        }
+        let Some(target) = self.shader_target.as_ref() else {
+            return;
+        };
+        let Some(material) = target.get_material() else {
+            return;
+        };
+        if let Ok(mut material) = material.try_cast::<ShaderMaterial>() {
+            material.set_shader_parameter(param, &value.to_variant());
+        }
+    }
+}
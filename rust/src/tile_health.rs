@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use godot::{classes::TileMapLayer, prelude::*};
+
+use crate::{
+    loot::LootTable,
+    map::{TilesMem, Vector2Mem},
+    pathfinding::pathfinder::Pathfinder,
+    pickable_manager::PickableManager,
+};
+
+// Tiles take damage until they break. Only tiles that have been hit, broken
+// or placed are tracked, the rest are as the level scene has them.
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct TileHealth {
+    base: Base<Node>,
+
+    #[export]
+    tiles: Option<Gd<TileMapLayer>>,
+
+    // For tiles without a "health" custom data value
+    #[export]
+    #[init(val = 10)]
+    default_health: u8,
+
+    // Rolled when a tile without a "loot_table" custom data value breaks
+    #[export]
+    default_loot_table: Option<Gd<LootTable>>,
+
+    damaged: HashMap<Vector2i, f32>,
+
+    // Source and atlas coords of placed tiles, None for broken ones
+    edited: HashMap<Vector2i, Option<(i32, Vector2i)>>,
+}
+
+#[godot_api]
+impl INode for TileHealth {
+    fn ready(&mut self) {
+        if self.tiles.is_none() {
+            godot_warn!("TileHealth: tiles are not set");
+        }
+        self.base_mut().add_to_group("tile_health");
+    }
+}
+
+#[godot_api]
+impl TileHealth {
+    // Tiles should be placed through here so the pathfinder sees them
+    #[func]
+    pub fn place(&mut self, cell: Vector2i, source_id: i32, atlas_coords: Vector2i) {
+        let Some(mut tiles) = self.tiles.clone() else {
+            return;
+        };
+        self.damaged.remove(&cell);
+        self.edited.insert(cell, Some((source_id, atlas_coords)));
+        tiles
+            .set_cell_ex(cell)
+            .source_id(source_id)
+            .atlas_coords(atlas_coords)
+            .done();
+        self.tile_changed(cell);
+    }
+}
+
+impl TileHealth {
+    pub fn find(node: &Gd<Node>) -> Option<Gd<TileHealth>> {
+        let mut tree = node.get_tree()?;
+        tree.get_first_node_in_group("tile_health")?
+            .try_cast::<TileHealth>()
+            .ok()
+    }
+
+    pub fn cell_at(&self, position: Vector2) -> Option<Vector2i> {
+        let tiles = self.tiles.as_ref()?;
+        let cell = tiles.local_to_map(tiles.to_local(position));
+        (tiles.get_cell_source_id(cell) != -1).then_some(cell)
+    }
+
+    fn max_health(&self, cell: Vector2i) -> f32 {
+        let Some(tiles) = self.tiles.as_ref() else {
+            return 0.0;
+        };
+        let has_layer = tiles
+            .get_tile_set()
+            .is_some_and(|tile_set| tile_set.get_custom_data_layer_by_name("health") >= 0);
+        let custom = tiles
+            .get_cell_tile_data(cell)
+            .filter(|_| has_layer)
+            .and_then(|data| data.get_custom_data("health").try_to::<i64>().ok());
+        custom.unwrap_or(self.default_health as i64) as f32
+    }
+
+    fn loot_table(&self, cell: Vector2i) -> Option<Gd<LootTable>> {
+        let tiles = self.tiles.as_ref()?;
+        let has_layer = tiles
+            .get_tile_set()
+            .is_some_and(|tile_set| tile_set.get_custom_data_layer_by_name("loot_table") >= 0);
+        let custom = tiles
+            .get_cell_tile_data(cell)
+            .filter(|_| has_layer)
+            .and_then(|data| {
+                data.get_custom_data("loot_table")
+                    .try_to::<Gd<LootTable>>()
+                    .ok()
+            });
+        custom.or_else(|| self.default_loot_table.clone())
+    }
+
+    pub fn health(&self, cell: Vector2i) -> f32 {
+        self.damaged
+            .get(&cell)
+            .copied()
+            .unwrap_or_else(|| self.max_health(cell))
+    }
+
+    // Returns true if the tile broke. `tool_tier` gates its loot like it does
+    // for resource nodes.
+    pub fn damage(&mut self, cell: Vector2i, amount: f32, tool_tier: u32) -> bool {
+        let Some(mut tiles) = self.tiles.clone() else {
+            return false;
+        };
+        if amount <= 0.0 || tiles.get_cell_source_id(cell) == -1 {
+            return false;
+        }
+        let left = self.health(cell) - amount;
+        if left > 0.0 {
+            self.damaged.insert(cell, left);
+            return false;
+        }
+        self.damaged.remove(&cell);
+        self.edited.insert(cell, None);
+        // Read before erasing, the tile data goes with the tile
+        let loot_table = self.loot_table(cell);
+        let position = tiles.to_global(tiles.map_to_local(cell));
+        tiles.erase_cell(cell);
+        if let Some(loot_table) = loot_table
+            && let Some(mut manager) = PickableManager::find(&self.to_gd().upcast())
+        {
+            manager
+                .bind_mut()
+                .spawn_loot(&loot_table, tool_tier, position);
+        }
+        self.tile_changed(cell);
+        true
+    }
+
+    fn tile_changed(&self, cell: Vector2i) {
+        if let Some(mut pathfinder) = Pathfinder::find(&self.to_gd().upcast()) {
+            pathfinder.bind_mut().tile_changed(cell);
+        }
+    }
+
+    // Damaged, broken and placed tiles, the rest are implied by the tile map
+    pub fn to_mem(&self) -> Vec<TilesMem> {
+        let Some(tiles) = self.tiles.as_ref() else {
+            return Vec::new();
+        };
+        let mut cells: Vec<Vector2i> = self.edited.keys().copied().collect();
+        cells.extend(
+            self.damaged
+                .keys()
+                .filter(|cell| !self.edited.contains_key(cell)),
+        );
+        cells
+            .into_iter()
+            .map(|cell| {
+                let source = tiles.get_cell_source_id(cell);
+                let atlas = tiles.get_cell_atlas_coords(cell);
+                let health = if source == -1 { 0.0 } else { self.health(cell) };
+                TilesMem {
+                    map_pos: Vector2Mem {
+                        x: cell.x as f32,
+                        y: cell.y as f32,
+                    },
+                    source,
+                    atlas_pos: Vector2Mem {
+                        x: atlas.x as f32,
+                        y: atlas.y as f32,
+                    },
+                    health: health.ceil().clamp(0.0, u8::MAX as f32) as u8,
+                }
+            })
+            .collect()
+    }
+
+    pub fn load_mem(&mut self, mems: &[TilesMem]) {
+        let Some(mut tiles) = self.tiles.clone() else {
+            return;
+        };
+        self.damaged.clear();
+        self.edited.clear();
+        for mem in mems {
+            let cell = Vector2i::new(mem.map_pos.x as i32, mem.map_pos.y as i32);
+            if mem.source == -1 || mem.health == 0 {
+                tiles.erase_cell(cell);
+                self.edited.insert(cell, None);
+            } else {
+                let atlas = Vector2i::new(mem.atlas_pos.x as i32, mem.atlas_pos.y as i32);
+                let placed = tiles.get_cell_source_id(cell) != mem.source
+                    || tiles.get_cell_atlas_coords(cell) != atlas;
+                if placed {
+                    tiles
+                        .set_cell_ex(cell)
+                        .source_id(mem.source)
+                        .atlas_coords(atlas)
+                        .done();
+                    self.edited.insert(cell, Some((mem.source, atlas)));
+                }
+                let health = mem.health as f32;
+                if health < self.max_health(cell) {
+                    self.damaged.insert(cell, health);
+                }
+            }
+            self.tile_changed(cell);
+        }
+    }
+}
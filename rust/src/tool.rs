@@ -0,0 +1,31 @@
+use godot::prelude::*;
+
+use crate::player::Player;
+
+// Anything the player can hold in the tool marker
+pub trait Tool {
+    fn tier(&self) -> u32 {
+        0
+    }
+}
+
+// Points the tool at the mouse and returns the aim direction. The player
+// turns to face the mouse as well while holding a tool, see `Player::set_dir`.
+pub fn aim_at_mouse(tool: &mut Node2D) -> Vector2 {
+    let mouse_pos = tool.get_global_mouse_position();
+    tool.look_at(mouse_pos);
+    (mouse_pos - tool.get_global_position()).normalized_or_zero()
+}
+
+// Takes one durability off the item the tool was taken from, see `Player::wear_tool`
+pub fn wear(tool: &Node2D) {
+    let Some(mut tree) = tool.get_tree() else {
+        return;
+    };
+    let Some(player) = tree.get_first_node_in_group("player") else {
+        return;
+    };
+    if let Ok(mut player) = player.try_cast::<Player>() {
+        player.bind_mut().wear_tool();
+    }
+}
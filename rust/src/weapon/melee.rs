@@ -0,0 +1,179 @@
+use godot::{
+    classes::{Area2D, Input},
+    prelude::*,
+};
+
+use crate::{
+    enemy::body::Enemy,
+    health::{DamageType, Health},
+    knockback::{self, Knockback},
+    tool::{self, Tool, aim_at_mouse},
+};
+
+#[derive(GodotClass)]
+#[class(init, base=Node2D)]
+pub struct MeleeWeapon {
+    base: Base<Node2D>,
+
+    #[export]
+    tier: u32,
+
+    // Swept along with the weapon, every enemy it touches is hit once per swing
+    #[export]
+    hitbox: Option<Gd<Area2D>>,
+
+    #[export]
+    #[init(val = 10.0)]
+    damage: f32,
+
+    #[export]
+    #[init(val = 250.0)]
+    knockback: f32,
+
+    // Seconds a hit enemy can't steer
+    #[export]
+    #[init(val = 0.2)]
+    knockback_stun: f32,
+
+    // Degrees covered by a swing, centred on the aim
+    #[export]
+    #[init(val = 120.0)]
+    swing_arc: f32,
+
+    // Seconds a swing takes
+    #[export]
+    #[init(val = 0.25)]
+    swing_time: f32,
+
+    // Seconds after a swing in which the next one carries on the combo
+    #[export]
+    #[init(val = 0.4)]
+    combo_window: f32,
+
+    // Damage multiplier of each swing of the combo, it starts over after the last
+    #[export]
+    #[init(val = PackedFloat32Array::from(&[1.0, 1.0, 1.5][..]))]
+    combo: PackedFloat32Array,
+
+    combo_step: usize,
+
+    // Seconds into the current swing, None between swings
+    swing: Option<f32>,
+
+    since_swing: f32,
+
+    aim_angle: f32,
+
+    // Already hit by the current swing
+    hit: Vec<InstanceId>,
+}
+
+#[godot_api]
+impl INode2D for MeleeWeapon {
+    fn ready(&mut self) {
+        if self.hitbox.is_none() {
+            godot_warn!("MeleeWeapon must have a hitbox");
+        }
+        self.set_hitbox_active(false);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let delta = delta as f32;
+        let Some(time) = self.swing else {
+            self.since_swing += delta;
+            let aim = aim_at_mouse(&mut self.base_mut());
+            if Input::singleton().is_action_just_pressed("tool_use") {
+                self.start_swing(aim.angle());
+            }
+            return;
+        };
+        let time = time + delta;
+        if time >= self.swing_time {
+            self.end_swing();
+            return;
+        }
+        self.swing = Some(time);
+        self.sweep(time);
+        self.hit_overlapping();
+    }
+}
+
+impl MeleeWeapon {
+    fn start_swing(&mut self, aim_angle: f32) {
+        if self.since_swing > self.combo_window {
+            self.combo_step = 0;
+        }
+        self.aim_angle = aim_angle;
+        self.swing = Some(0.0);
+        self.hit.clear();
+        self.set_hitbox_active(true);
+        self.sweep(0.0);
+        tool::wear(&self.base());
+    }
+
+    fn end_swing(&mut self) {
+        self.swing = None;
+        self.since_swing = 0.0;
+        self.combo_step = (self.combo_step + 1) % self.combo.len().max(1);
+        self.set_hitbox_active(false);
+    }
+
+    // Every other swing of a combo goes back the way the previous one came
+    fn sweep(&mut self, time: f32) {
+        let progress = (time / self.swing_time.max(f32::EPSILON)).clamp(0.0, 1.0);
+        let direction = if self.combo_step.is_multiple_of(2) {
+            1.0
+        } else {
+            -1.0
+        };
+        let offset = direction * (progress - 0.5) * self.swing_arc.to_radians();
+        let angle = self.aim_angle + offset;
+        self.base_mut().set_global_rotation(angle);
+    }
+
+    fn hit_overlapping(&mut self) {
+        let Some(hitbox) = self.hitbox.as_ref() else {
+            return;
+        };
+        let multiplier = self.combo.get(self.combo_step).unwrap_or(1.0);
+        let damage = self.damage * multiplier;
+        let origin = self.base().get_global_position();
+        for body in hitbox.get_overlapping_bodies().iter_shared() {
+            if !body.is_in_group("enemy") || self.hit.contains(&body.instance_id()) {
+                continue;
+            }
+            self.hit.push(body.instance_id());
+            let Some(mut health) = Health::of(&body.clone().upcast()) else {
+                continue;
+            };
+            if !health.bind_mut().damage(damage, DamageType::Physical) {
+                continue;
+            }
+            if let Ok(mut enemy) = body.clone().try_cast::<Enemy>() {
+                enemy.bind_mut().hit_with(self.tier());
+            }
+            let away = (body.get_global_position() - origin).normalized_or_zero();
+            knockback::apply(
+                body,
+                Knockback {
+                    direction: away,
+                    strength: self.knockback,
+                    stun: self.knockback_stun,
+                },
+            );
+        }
+    }
+
+    fn set_hitbox_active(&mut self, active: bool) {
+        if let Some(hitbox) = self.hitbox.as_mut() {
+            hitbox.set_monitoring(active);
+        }
+    }
+}
+
+#[godot_dyn]
+impl Tool for MeleeWeapon {
+    fn tier(&self) -> u32 {
+        self.tier
+    }
+}
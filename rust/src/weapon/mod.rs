@@ -0,0 +1,2 @@
+pub mod melee;
+pub mod ranged;
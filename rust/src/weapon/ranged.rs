@@ -0,0 +1,130 @@
+use godot::{
+    classes::{Input, RandomNumberGenerator},
+    prelude::*,
+};
+
+use crate::{
+    inventory::item::InventoryItem,
+    player::Player,
+    projectile_manager::ProjectileManager,
+    tool::{self, Tool, aim_at_mouse},
+};
+
+#[derive(GodotClass)]
+#[class(init, base=Node2D)]
+pub struct RangedWeapon {
+    base: Base<Node2D>,
+
+    #[export]
+    tier: u32,
+
+    // One is taken from the player's inventory per shot, unset fires for free
+    #[export]
+    ammo: Option<Gd<InventoryItem>>,
+
+    // Damage and the like come from the projectile scene
+    #[export]
+    projectile_scene: Option<Gd<PackedScene>>,
+
+    // Where projectiles leave from, the weapon's origin when unset
+    #[export]
+    muzzle: Option<Gd<Node2D>>,
+
+    // Pixels per second
+    #[export]
+    #[init(val = 600.0)]
+    projectile_speed: f32,
+
+    // Seconds between shots
+    #[export]
+    #[init(val = 0.5)]
+    fire_interval: f32,
+
+    // Keeps firing while `tool_use` is held instead of once per press
+    #[export]
+    automatic: bool,
+
+    // Degrees a shot can stray from the aim either way
+    #[export]
+    spread: f32,
+
+    fire_timer: f32,
+
+    #[init(val = RandomNumberGenerator::new_gd())]
+    rng: Gd<RandomNumberGenerator>,
+}
+
+#[godot_api]
+impl INode2D for RangedWeapon {
+    fn ready(&mut self) {
+        if self.projectile_scene.is_none() {
+            godot_warn!("RangedWeapon must have a projectile scene");
+        }
+        self.rng.randomize();
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        self.fire_timer = (self.fire_timer - delta as f32).max(0.0);
+        let aim = aim_at_mouse(&mut self.base_mut());
+        let input = Input::singleton();
+        let wants_to_fire = if self.automatic {
+            input.is_action_pressed("tool_use")
+        } else {
+            input.is_action_just_pressed("tool_use")
+        };
+        if wants_to_fire && self.fire_timer <= 0.0 {
+            self.fire(aim);
+        }
+    }
+}
+
+impl RangedWeapon {
+    fn player(&self) -> Option<Gd<Player>> {
+        let mut tree = self.base().get_tree()?;
+        tree.get_first_node_in_group("player")?
+            .try_cast::<Player>()
+            .ok()
+    }
+
+    fn fire(&mut self, aim: Vector2) {
+        let Some(scene) = self.projectile_scene.clone() else {
+            return;
+        };
+        let Some(mut manager) = ProjectileManager::find(&self.to_gd().upcast()) else {
+            return;
+        };
+        let player = self.player();
+        if let Some(ammo) = self.ammo.as_ref() {
+            let Some(mut player) = player.clone() else {
+                return;
+            };
+            if !player.bind_mut().take_ammo(ammo) {
+                return;
+            }
+        }
+        self.fire_timer = self.fire_interval;
+        tool::wear(&self.base());
+
+        let spread = self.spread.to_radians();
+        let angle = aim.angle() + self.rng.randf_range(-spread, spread);
+        let velocity = Vector2::from_angle(angle) * self.projectile_speed;
+        let origin = self
+            .muzzle
+            .as_ref()
+            .map_or(self.base().get_global_position(), |muzzle| {
+                muzzle.get_global_position()
+            });
+        let source = player.map(|player| player.instance_id());
+        let tier = self.tier();
+        manager
+            .bind_mut()
+            .spawn(&scene, origin, velocity, source, tier);
+    }
+}
+
+#[godot_dyn]
+impl Tool for RangedWeapon {
+    fn tier(&self) -> u32 {
+        self.tier
+    }
+}